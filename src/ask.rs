@@ -0,0 +1,293 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::future::Future;
+
+use crate::bounded;
+use crate::channel;
+use crate::error::*;
+use crate::mpmc;
+use crate::once;
+use crate::priority;
+use crate::timer::Delay;
+use crate::timer::Timer;
+use crate::unbounded;
+
+/// A sender over which requests expecting a reply can be
+/// sent, wrapped in an [`Envelope`].
+///
+/// It is implemented for mutable references to the
+/// senders of bounded, unbounded, multi-consumer,
+/// priority and configurable channels.
+///
+/// [`Envelope`]: struct.Envelope.html
+pub trait Ask<Q, R>: Sized {
+    /// The future sending an envelope over the channel.
+    type Send: Future<Output = Result<(), SendError<Envelope<Q, R>>>> + Unpin;
+
+    /// Returns a future that sends `envelope` over the
+    /// channel (see, e.g., [`bounded::Sender::send`]).
+    ///
+    /// [`bounded::Sender::send`]: ../bounded/struct.Sender.html#method.send
+    fn send_envelope(self, envelope: Envelope<Q, R>) -> Self::Send;
+
+    /// Returns a future that sends `request` over the
+    /// channel in an [`Envelope`] (see [`send_envelope`]),
+    /// and resolves to `Ok(R)` with the reply to it, or
+    /// either to `Err(AskError::Send)` if the request
+    /// couldn't be sent or to `Err(AskError::Dropped)` if
+    /// the envelope has been dropped without a reply being
+    /// sent.
+    ///
+    /// [`Envelope`]: struct.Envelope.html
+    /// [`send_envelope`]: #tymethod.send_envelope
+    fn ask(self, request: Q) -> AskFuture<Self::Send, R> {
+        let (envelope, reply) = Envelope::new(request);
+        AskFuture::new(self.send_envelope(envelope), reply)
+    }
+
+    /// Returns a future that sends `request` and resolves
+    /// with the reply to it (see [`ask`]), or resolves to
+    /// `Err(AskError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`ask`]: #method.ask
+    fn ask_timeout<T: Timer>(
+        self,
+        request: Q,
+        timeout: Duration,
+        timer: T,
+    ) -> AskTimeoutFuture<Self::Send, R, T> {
+        let deadline = timer.now() + timeout;
+        self.ask_deadline(request, deadline, timer)
+    }
+
+    /// Returns a future that sends `request` and resolves
+    /// with the reply to it (see [`ask`]), or resolves to
+    /// `Err(AskError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`ask`]: #method.ask
+    fn ask_deadline<T: Timer>(
+        self,
+        request: Q,
+        deadline: Instant,
+        timer: T,
+    ) -> AskTimeoutFuture<Self::Send, R, T> {
+        AskTimeoutFuture {
+            ask: self.ask(request),
+            delay: Delay::new(deadline, timer),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A request sent by [`Ask::ask`], along with the handle
+/// used to reply to it.
+///
+/// [`Ask::ask`]: trait.Ask.html#method.ask
+pub struct Envelope<Q, R> {
+    /// The request.
+    pub request: Q,
+    /// The sending half of the single use channel over
+    /// which the reply is expected.
+    pub reply: once::Sender<R>,
+}
+
+#[derive(Debug)]
+/// A future that sends a request in an [`Envelope`] and
+/// resolves with the reply to it (see [`Ask::ask`]).
+///
+/// [`Envelope`]: struct.Envelope.html
+/// [`Ask::ask`]: trait.Ask.html#method.ask
+pub struct AskFuture<F, R> {
+    /// The future sending the request, until it has been
+    /// sent.
+    send: Option<F>,
+    reply: once::Receiver<R>,
+}
+
+#[derive(Debug)]
+/// A future that sends a request in an [`Envelope`] and
+/// resolves with the reply to it, unless its deadline is
+/// reached first (see [`Ask::ask_timeout`]).
+///
+/// [`Envelope`]: struct.Envelope.html
+/// [`Ask::ask_timeout`]: trait.Ask.html#method.ask_timeout
+pub struct AskTimeoutFuture<F, R, T: Timer> {
+    ask: AskFuture<F, R>,
+    delay: Delay<T>,
+}
+
+#[derive(Debug)]
+/// A future that resolves with the result of sending an
+/// envelope over an unbounded channel, which is known as
+/// soon as it is sent (see [`Ask::send_envelope`]).
+///
+/// [`Ask::send_envelope`]: trait.Ask.html#tymethod.send_envelope
+pub struct SentFuture<D> {
+    res: Option<Result<(), SendError<D>>>,
+}
+
+impl<Q, R> Envelope<Q, R> {
+    /// Creates a new envelope containing `request`, along
+    /// with the receiving half of the single use channel
+    /// over which the reply will be sent.
+    pub fn new(request: Q) -> (Envelope<Q, R>, once::Receiver<R>) {
+        let (reply, receiver) = once::new();
+
+        (Envelope { request, reply }, receiver)
+    }
+
+    /// Sends `data` as the reply to the request (see
+    /// [`once::Sender::send`]).
+    ///
+    /// [`once::Sender::send`]: ../once/struct.Sender.html#method.send
+    pub fn reply(&mut self, data: R) -> Result<(), SendError<R>> {
+        self.reply.send(data)
+    }
+}
+
+impl<F, R> AskFuture<F, R> {
+    fn new(send: F, reply: once::Receiver<R>) -> AskFuture<F, R> {
+        AskFuture {
+            send: Some(send),
+            reply,
+        }
+    }
+}
+
+impl<'s, Q, R> Ask<Q, R> for &'s mut bounded::Sender<Envelope<Q, R>> {
+    type Send = bounded::SendFuture<'s, Envelope<Q, R>>;
+
+    fn send_envelope(self, envelope: Envelope<Q, R>) -> Self::Send {
+        self.send(envelope)
+    }
+}
+
+impl<Q, R> Ask<Q, R> for &mut unbounded::Sender<Envelope<Q, R>> {
+    type Send = SentFuture<Envelope<Q, R>>;
+
+    fn send_envelope(self, envelope: Envelope<Q, R>) -> Self::Send {
+        SentFuture {
+            res: Some(self.send(envelope)),
+        }
+    }
+}
+
+impl<'s, Q, R> Ask<Q, R> for &'s mut mpmc::Sender<Envelope<Q, R>> {
+    type Send = mpmc::SendFuture<'s, Envelope<Q, R>>;
+
+    fn send_envelope(self, envelope: Envelope<Q, R>) -> Self::Send {
+        self.send(envelope)
+    }
+}
+
+impl<'s, Q, R> Ask<Q, R> for &'s mut priority::Sender<Envelope<Q, R>> {
+    type Send = priority::SendFuture<'s, Envelope<Q, R>>;
+
+    fn send_envelope(self, envelope: Envelope<Q, R>) -> Self::Send {
+        self.send(envelope)
+    }
+}
+
+impl<'s, Q, R> Ask<Q, R> for &'s mut channel::Sender<Envelope<Q, R>> {
+    type Send = channel::SendFuture<'s, Envelope<Q, R>>;
+
+    fn send_envelope(self, envelope: Envelope<Q, R>) -> Self::Send {
+        self.send(envelope)
+    }
+}
+
+impl<Q, R> priority::Sender<Envelope<Q, R>> {
+    /// Returns a future that sends `request` over `lane`
+    /// and resolves with the reply to it (see
+    /// [`Ask::ask`]).
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the channel doesn't have
+    /// this lane.
+    ///
+    /// [`Ask::ask`]: ../ask/trait.Ask.html#method.ask
+    pub fn ask_to(
+        &mut self,
+        lane: usize,
+        request: Q,
+    ) -> AskFuture<priority::SendFuture<'_, Envelope<Q, R>>, R> {
+        let (envelope, reply) = Envelope::new(request);
+        AskFuture::new(self.send_to(lane, envelope), reply)
+    }
+}
+
+impl<F, R> Unpin for AskFuture<F, R> {}
+impl<F, R, T: Timer> Unpin for AskTimeoutFuture<F, R, T> {}
+impl<D> Unpin for SentFuture<D> {}
+
+impl<F, Q, R> Future for AskFuture<F, R>
+where
+    F: Future<Output = Result<(), SendError<Envelope<Q, R>>>> + Unpin,
+{
+    type Output = Result<R, AskError<Q>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<R, AskError<Q>>> {
+        let fut = self.get_mut();
+        if let Some(ref mut send) = fut.send {
+            match Pin::new(send).poll(cx) {
+                Poll::Ready(Ok(())) => fut.send = None,
+                Poll::Ready(Err(err)) => {
+                    fut.send = None;
+                    let err = err.map_inner(|envelope| envelope.request);
+                    return Poll::Ready(Err(AskError::Send(err)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        match Pin::new(&mut fut.reply).poll(cx) {
+            Poll::Ready(Ok(data)) => Poll::Ready(Ok(data)),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(AskError::Dropped(err))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<D> Future for SentFuture<D> {
+    type Output = Result<(), SendError<D>>;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        Poll::Ready(
+            self.get_mut()
+                .res
+                .take()
+                .expect("the future has already resolved"),
+        )
+    }
+}
+
+impl<F, Q, R, T> Future for AskTimeoutFuture<F, R, T>
+where
+    F: Future<Output = Result<(), SendError<Envelope<Q, R>>>> + Unpin,
+    T: Timer,
+{
+    type Output = Result<R, AskError<Q>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<R, AskError<Q>>> {
+        // NOTE: if the deadline is reached before the request
+        //   has been sent, it is dropped along with the
+        //   future.
+        let fut = self.get_mut();
+        match Pin::new(&mut fut.ask).poll(cx) {
+            Poll::Pending => (),
+            poll => return poll,
+        }
+
+        match fut.delay.poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(AskError::Timeout)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
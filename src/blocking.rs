@@ -0,0 +1,159 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::RawWaker;
+use std::task::RawWakerVTable;
+use std::task::Waker;
+use std::thread;
+use std::thread::Thread;
+use std::time::Instant;
+
+use futures_core::future::Future;
+
+use crate::error::*;
+use crate::future::PollSend;
+use crate::future::RecvFuture;
+use crate::select::Selectable;
+
+/// Blocks the current thread until `poll` returns
+/// `Poll::Ready`, parking it while `poll` returns
+/// `Poll::Pending`.
+pub(crate) fn wait<T, P>(mut poll: P) -> T
+where
+    P: FnMut(&mut Context) -> Poll<T>,
+{
+    let waker = thread_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(res) = poll(&mut cx) {
+            return res;
+        }
+
+        thread::park();
+    }
+}
+
+/// Blocks the current thread until `poll` returns
+/// `Poll::Ready` (see [`wait`]), returning `None` if it
+/// didn't before `deadline`.
+///
+/// [`wait`]: fn.wait.html
+pub(crate) fn wait_until<T, P>(deadline: Instant, mut poll: P) -> Option<T>
+where
+    P: FnMut(&mut Context) -> Poll<T>,
+{
+    let waker = thread_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(res) = poll(&mut cx) {
+            return Some(res);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return None;
+        }
+
+        thread::park_timeout(deadline - now);
+    }
+}
+
+/// Sends `data` with `sender`, blocking the current
+/// thread until it has been sent (see [`wait`]).
+///
+/// [`wait`]: fn.wait.html
+pub(crate) fn send<S, D>(sender: &mut S, data: D) -> Result<(), SendError<D>>
+where
+    S: PollSend<D>,
+{
+    let mut data = Some(data);
+    wait(|cx| sender.poll_send(cx, &mut data))
+}
+
+/// Sends `data` with `sender` (see [`send`]), returning
+/// `Err(SendError::Timeout)` if it couldn't before
+/// `deadline`.
+///
+/// [`send`]: fn.send.html
+pub(crate) fn send_until<S, D>(
+    sender: &mut S,
+    data: D,
+    deadline: Instant,
+) -> Result<(), SendError<D>>
+where
+    S: PollSend<D>,
+{
+    let mut data = Some(data);
+    match wait_until(deadline, |cx| sender.poll_send(cx, &mut data)) {
+        Some(res) => res,
+        // NOTE: once the message has been handed off to the
+        //   receiver of a rendezvous channel, it can't be
+        //   given back anymore, so the sender keeps waiting
+        //   for the receiver to take it.
+        None if data.is_none() => wait(|cx| sender.poll_send(cx, &mut data)),
+        None => Err(SendError::Timeout(data.take().unwrap())),
+    }
+}
+
+/// Receives a message with `receiver`, blocking the
+/// current thread until there is one available (see
+/// [`wait`]).
+///
+/// [`wait`]: fn.wait.html
+pub(crate) fn recv<R: Selectable>(receiver: &mut R) -> Result<R::Item, ReceiveError> {
+    let mut fut = RecvFuture::new(receiver);
+    wait(|cx| Pin::new(&mut fut).poll(cx))
+}
+
+/// Receives a message with `receiver` (see [`recv`]),
+/// returning `Err(ReceiveError::Timeout)` if none has
+/// been received before `deadline`.
+///
+/// [`recv`]: fn.recv.html
+pub(crate) fn recv_until<R: Selectable>(
+    receiver: &mut R,
+    deadline: Instant,
+) -> Result<R::Item, ReceiveError> {
+    let mut fut = RecvFuture::new(receiver);
+    wait_until(deadline, |cx| Pin::new(&mut fut).poll(cx)).unwrap_or(Err(ReceiveError::Timeout))
+}
+
+/// Creates a waker that unparks the current thread.
+fn thread_waker() -> Waker {
+    let thread = Arc::new(thread::current());
+
+    // SAFETY: the vtable's functions uphold `RawWaker`'s
+    //   contract for a pointer created by `Arc::into_raw`.
+    unsafe { Waker::from_raw(raw_waker(thread)) }
+}
+
+fn raw_waker(thread: Arc<Thread>) -> RawWaker {
+    RawWaker::new(Arc::into_raw(thread) as *const (), &VTABLE)
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
+
+unsafe fn clone_waker(ptr: *const ()) -> RawWaker {
+    let thread = Arc::from_raw(ptr as *const Thread);
+    let cloned = thread.clone();
+    // NOTE: the waker that is being cloned still owns
+    //   its reference.
+    let _ = Arc::into_raw(thread);
+
+    raw_waker(cloned)
+}
+
+unsafe fn wake(ptr: *const ()) {
+    Arc::from_raw(ptr as *const Thread).unpark();
+}
+
+unsafe fn wake_by_ref(ptr: *const ()) {
+    (*(ptr as *const Thread)).unpark();
+}
+
+unsafe fn drop_waker(ptr: *const ()) {
+    drop(Arc::from_raw(ptr as *const Thread));
+}
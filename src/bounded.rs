@@ -1,101 +1,352 @@
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 
-use futures_channel::mpsc;
-use futures_channel::mpsc::Receiver as FutReceiver;
-use futures_channel::mpsc::Sender as FutSender;
 use futures_core::stream::FusedStream;
 use futures_core::stream::Stream;
 use futures_sink::Sink;
 
+use crate::blocking;
 use crate::error::*;
+use crate::future;
+use crate::future::PollSend;
+use crate::inner;
+use crate::inner::Inner;
+use crate::receipt::Receipt;
+use crate::timer::Timer;
 
-/// Creates a new bounded channel (see [`futures-channel`'s
-/// documentation]).
+/// Creates a new bounded channel, which can hold exactly
+/// `buf` messages, no matter how many senders it has.
 ///
-/// [`futures-channel`'s documentation]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/mpsc/fn.channel.html
+/// If `buf` is `0`, the channel is a rendezvous channel,
+/// which doesn't buffer any message: a message can only
+/// be sent once the receiver is waiting for one (see
+/// [`Sender::send`] and [`Sender::try_send`]).
+///
+/// [`Sender::send`]: struct.Sender.html#method.send
+/// [`Sender::try_send`]: struct.Sender.html#method.try_send
 pub fn new<D>(buf: usize) -> (Sender<D>, Receiver<D>) {
-    let (sender, receiver) = mpsc::channel(buf);
+    let inner = Arc::new(Inner::new(Some(buf)));
 
-    (Sender::new(buf, sender), Receiver::new(buf, receiver))
+    (Sender::new(buf, inner.clone()), Receiver::new(buf, inner))
 }
 
-#[derive(Debug)]
-/// A wrapper around a [`mpsc::Sender`] that stores its state
-/// after sending data, closing the channel or disconnecting
-/// itself.
+/// Creates a new bounded channel (see [`new`]), which
+/// can have at most `max_senders` senders at the same
+/// time.
+///
+/// If `max_senders` is `1`, the channel uses a faster
+/// single-producer queue.
+///
+/// # Panics
+///
+/// This function panics if `max_senders` is `0`.
+///
+/// [`new`]: fn.new.html
+pub fn with_max_senders<D>(buf: usize, max_senders: usize) -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(Some(buf)).with_limits(Some(max_senders), Some(1)));
+
+    (Sender::new(buf, inner.clone()), Receiver::new(buf, inner))
+}
+
+/// Creates a new bounded channel (see [`new`]), which
+/// handles the messages sent while its buffer is full
+/// as defined by `overflow`.
+///
+/// # Panics
+///
+/// This function panics if `buf` is `0` and `overflow`
+/// is either `Overflow::DropOldest` or
+/// `Overflow::DropNewest`.
+///
+/// [`new`]: fn.new.html
+pub fn with_overflow<D>(buf: usize, overflow: Overflow) -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(Some(buf)).with_overflow(overflow, None));
+
+    (Sender::new(buf, inner.clone()), Receiver::new(buf, inner))
+}
+
+/// Creates a new bounded channel (see [`with_overflow`]),
+/// which passes the messages it evicts to `on_evict`.
+///
+/// `on_evict` is called by the sender whose message
+/// caused the eviction, unless it is sent using
+/// [`Sender::try_send_evicting`], which returns the
+/// evicted message instead.
+///
+/// # Panics
 ///
-/// [`mpsc::Sender`]: https://rust-lang-nursery.github.io/futures-api-docs/0.3.0-alpha.15/futures/channel/mpsc/struct.Sender.html
+/// See [`with_overflow`].
+///
+/// [`with_overflow`]: fn.with_overflow.html
+/// [`Sender::try_send_evicting`]: struct.Sender.html#method.try_send_evicting
+pub fn with_evict_callback<D, F>(
+    buf: usize,
+    overflow: Overflow,
+    on_evict: F,
+) -> (Sender<D>, Receiver<D>)
+where
+    F: Fn(D) + Send + Sync + 'static,
+{
+    let inner = Arc::new(Inner::new(Some(buf)).with_overflow(overflow, Some(Box::new(on_evict))));
+
+    (Sender::new(buf, inner.clone()), Receiver::new(buf, inner))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// What happens when a message is sent over a bounded
+/// channel while its buffer is full.
+///
+/// The channels created with [`new`] use
+/// `Overflow::Block`.
+///
+/// [`new`]: fn.new.html
+pub enum Overflow {
+    /// The message is rejected: both [`Sender::try_send`]
+    /// and [`Sender::send`] fail with `SendError::Full`.
+    ///
+    /// [`Sender::try_send`]: struct.Sender.html#method.try_send
+    /// [`Sender::send`]: struct.Sender.html#method.send
+    Reject,
+    /// The oldest message of the buffer is evicted to make
+    /// room for the new one.
+    DropOldest,
+    /// The new message is evicted, as if it had been sent
+    /// and dropped right away.
+    DropNewest,
+    /// [`Sender::try_send`] fails with `SendError::Full`,
+    /// while [`Sender::send`] waits for a slot to be
+    /// freed.
+    ///
+    /// [`Sender::try_send`]: struct.Sender.html#method.try_send
+    /// [`Sender::send`]: struct.Sender.html#method.send
+    Block,
+}
+
+#[derive(Debug)]
+/// The sending half of a bounded channel, which shares
+/// the channel's state with the other senders and the
+/// receiver.
 pub struct Sender<D> {
     /// The size of the buffer (as it was provided to
     /// [`bounded`])
     ///
     /// [`bounded`]: ../fn.bounded.html
     pub buf: usize,
-    /// Whether the channel has been closed.
-    pub closed: bool,
-    /// Whether the sender has disconnected itself from the
-    /// channel.
-    pub disconnected: bool,
-    sender: FutSender<D>,
+    sender: inner::Sender<D>,
 }
 
-#[derive(Debug)]
-/// A wrapper around a [`mpsc::Receiver`] that stores its
-/// state after trying to receive data or closing the channel.
+/// A future that sends data over a bounded channel
+/// once there is a free slot in its buffer (see
+/// [`Sender::send`]).
 ///
-/// [`mpsc::Receiver`]: https://rust-lang-nursery.github.io/futures-api-docs/0.3.0-alpha.15/futures/channel/mpsc/struct.Receiver.html
+/// [`Sender::send`]: struct.Sender.html#method.send
+pub type SendFuture<'s, D> = future::SendFuture<'s, Sender<D>, D>;
+
+#[derive(Debug)]
+/// The receiving half of a bounded channel, which shares
+/// the channel's state with the senders.
 pub struct Receiver<D> {
     /// The size of the buffer (as it was provided to
     /// [`bounded`])
     ///
     /// [`bounded`]: ../fn.bounded.html
     pub buf: usize,
-    /// Whether the channel has been closed.
-    pub closed: bool,
-    receiver: Option<FutReceiver<D>>,
+    receiver: inner::Receiver<D>,
 }
 
+/// A future that receives a message over a bounded
+/// channel (see [`Receiver::recv`]).
+///
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+pub type RecvFuture<'r, D> = future::RecvFuture<'r, Receiver<D>>;
+
+/// A future that sends data over a bounded channel
+/// once there is a free slot in its buffer, unless its
+/// deadline is reached first (see [`Sender::send_within`]).
+///
+/// [`Sender::send_within`]: struct.Sender.html#method.send_within
+pub type SendTimeoutFuture<'s, D, T> = future::SendTimeoutFuture<'s, Sender<D>, D, T>;
+
+/// A future that receives a message over a bounded
+/// channel, unless its deadline is reached first (see
+/// [`Receiver::recv_within`]).
+///
+/// [`Receiver::recv_within`]: struct.Receiver.html#method.recv_within
+pub type RecvTimeoutFuture<'r, D, T> = future::RecvTimeoutFuture<'r, Receiver<D>, T>;
+
 impl<D> Sender<D> {
-    pub(crate) fn new(buf: usize, sender: FutSender<D>) -> Sender<D> {
+    pub(crate) fn new(buf: usize, inner: Arc<Inner<D>>) -> Sender<D> {
         Sender {
             buf,
-            closed: false,
-            disconnected: false,
-            sender,
+            sender: inner::Sender::new(inner),
         }
     }
 
-    /// Sends `data` over the channel, returning `Ok(())` if
-    /// it has been successfully sent, or either
+    /// Whether the channel has been closed (by any of
+    /// the senders or by the receiver).
+    pub fn is_closed(&self) -> bool {
+        self.sender.inner().is_closed()
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.sender.is_disconnected()
+    }
+
+    /// What happens when a message is sent while the
+    /// channel's buffer is full.
+    pub fn overflow(&self) -> Overflow {
+        self.sender.inner().overflow()
+    }
+
+    /// The number of messages that the channel has evicted
+    /// because its buffer was full.
+    pub fn evicted(&self) -> usize {
+        self.sender.inner().evicted()
+    }
+
+    /// Tries to send `data` over the channel, returning
+    /// `Ok(())` if it has been successfully sent, or either
     /// `Err(SendError::Full)` if the channel's buffer is full,
     /// `Err(SendError::Disconnected)` if the  sender has
     /// disconnected itself from the channel or
     /// `Err(SendError::Closed)` if the channel has been closed.
-    pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
-        if self.disconnected {
-            return Err(SendError::Disconnected(data));
-        } else if self.closed {
-            return Err(SendError::Closed(data));
-        } else if self.sender.is_closed() {
-            self.closed = true;
-            return Err(SendError::Closed(data));
-        }
+    ///
+    /// If the channel is a rendezvous channel, this only
+    /// succeeds if the receiver is already waiting for a
+    /// message (and returns `Err(SendError::Full)` if it
+    /// isn't).
+    ///
+    /// If the channel evicts messages when its buffer is
+    /// full (see [`Overflow`]), this succeeds and passes the
+    /// evicted message to the channel's eviction callback
+    /// (if any).
+    ///
+    /// [`Overflow`]: enum.Overflow.html
+    pub fn try_send(&mut self, data: D) -> Result<(), SendError<D>> {
+        self.sender.try_send(data)
+    }
 
-        match self.sender.try_send(data) {
-            Ok(()) => Ok(()),
-            Err(err) => {
-                if err.is_disconnected() {
-                    self.closed = true;
-                    Err(SendError::Closed(err.into_inner()))
-                } else if err.is_full() {
-                    Err(SendError::Full(err.into_inner()))
-                } else {
-                    unreachable!();
-                }
-            }
-        }
+    /// Tries to send `data` over the channel (see
+    /// [`try_send`]), returning `Ok(Some(D))` with the
+    /// message that has been evicted to make room for it
+    /// (or with `data` itself if the channel drops the
+    /// newest messages), or `Ok(None)` if none has been.
+    ///
+    /// Unlike with [`try_send`], the evicted message isn't
+    /// passed to the channel's eviction callback.
+    ///
+    /// [`try_send`]: #method.try_send
+    pub fn try_send_evicting(&mut self, data: D) -> Result<Option<D>, SendError<D>> {
+        self.sender.try_send_evicting(data)
+    }
+
+    /// Tries to send `data` over the channel (see
+    /// [`try_send`]), returning a [`Receipt`] that resolves
+    /// once it has been received if it has been successfully
+    /// sent.
+    ///
+    /// [`try_send`]: #method.try_send
+    /// [`Receipt`]: ../receipt/struct.Receipt.html
+    pub fn try_send_notify(&mut self, data: D) -> Result<Receipt, SendError<D>> {
+        self.sender.try_send_notify(data)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// once there is a free slot in its buffer, resolving
+    /// to `Ok(())` if it has been successfully sent, or
+    /// either `Err(SendError::Disconnected)` if the sender
+    /// has disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been
+    /// closed in the meantime.
+    ///
+    /// If the channel is a rendezvous channel, the future
+    /// waits for the receiver to be waiting for a message,
+    /// hands it off to it and resolves once the receiver
+    /// has taken it (or has been dropped without taking
+    /// it).
+    ///
+    /// The future only waits if the channel's overflow
+    /// policy is `Overflow::Block`, and otherwise resolves
+    /// right away (see [`try_send`]).
+    ///
+    /// [`try_send`]: #method.try_send
+    pub fn send(&mut self, data: D) -> SendFuture<'_, D> {
+        SendFuture::new(self, data)
+    }
+
+    /// Sends `data` over the channel (see [`send`]),
+    /// blocking the current thread until there is a free
+    /// slot in the channel's buffer (or, if the channel is
+    /// a rendezvous channel, until the receiver has taken
+    /// the message).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_blocking(&mut self, data: D) -> Result<(), SendError<D>> {
+        blocking::send(self, data)
+    }
+
+    /// Sends `data` over the channel (see
+    /// [`send_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(SendError::Timeout)` if it couldn't be sent in
+    /// time.
+    ///
+    /// If the channel is a rendezvous channel and the data
+    /// has been handed off to the receiver before `timeout`
+    /// elapsed, the thread keeps blocking until the receiver
+    /// takes it.
+    ///
+    /// [`send_blocking`]: #method.send_blocking
+    pub fn send_timeout(&mut self, data: D, timeout: Duration) -> Result<(), SendError<D>> {
+        blocking::send_until(self, data, Instant::now() + timeout)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// (see [`send`]), or resolves to
+    /// `Err(SendError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_within<T: Timer>(
+        &mut self,
+        data: D,
+        timeout: Duration,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.send_deadline(data, deadline, timer)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// (see [`send`]), or resolves to
+    /// `Err(SendError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_deadline<T: Timer>(
+        &mut self,
+        data: D,
+        deadline: Instant,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        SendTimeoutFuture::new(self, data, deadline, timer)
+    }
+
+    /// Tries to clone the sender, returning the new sender if
+    /// it succeeded, or `Err(CloneError::Limit)` if the
+    /// channel already has the maximum number of senders
+    /// that it can have.
+    pub fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        Ok(Sender {
+            buf: self.buf,
+            sender: self.sender.try_clone()?,
+        })
     }
 
     /// Tries to disconnect the sender from the channel,
@@ -105,18 +356,7 @@ impl<D> Sender<D> {
     /// `Err(DisconnectError::Closed)` if the channel was
     /// already closed.
     pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
-        if self.disconnected {
-            Err(DisconnectError::Disconnected)
-        } else if self.closed {
-            Err(DisconnectError::Closed)
-        } else if self.sender.is_closed() {
-            self.closed = true;
-            Err(DisconnectError::Closed)
-        } else {
-            self.sender.disconnect();
-            self.disconnected = true;
-            Ok(())
-        }
+        self.sender.disconnect()
     }
 
     /// Tries to close the channel, returning `Ok(())` if it
@@ -125,164 +365,220 @@ impl<D> Sender<D> {
     /// `Err(CloseError::Closed)` if the channel was already
     /// closed.
     pub fn close(&mut self) -> Result<(), CloseError> {
-        if self.disconnected {
-            Err(CloseError::Disconnected)
-        } else if self.closed {
-            Err(CloseError::Closed)
-        } else if self.sender.is_closed() {
-            self.closed = true;
-            Err(CloseError::Closed)
-        } else {
-            self.sender.close_channel();
-            self.closed = true;
-            Ok(())
-        }
+        self.sender.close()
     }
 }
 
 impl<D> Receiver<D> {
-    pub(crate) fn new(buf: usize, receiver: FutReceiver<D>) -> Receiver<D> {
+    pub(crate) fn new(buf: usize, inner: Arc<Inner<D>>) -> Receiver<D> {
         Receiver {
             buf,
-            closed: false,
-            receiver: Some(receiver),
+            receiver: inner::Receiver::new(inner),
         }
     }
 
+    /// Whether the channel has been closed (by the
+    /// receiver or by any of the senders).
+    pub fn is_closed(&self) -> bool {
+        self.receiver.inner().is_closed()
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.receiver.is_disconnected()
+    }
+
+    /// What happens when a message is sent while the
+    /// channel's buffer is full.
+    pub fn overflow(&self) -> Overflow {
+        self.receiver.inner().overflow()
+    }
+
+    /// The number of messages that the channel has evicted
+    /// because its buffer was full.
+    pub fn evicted(&self) -> usize {
+        self.receiver.inner().evicted()
+    }
+
     /// Tries to receive a message over the channel, returning
     /// `Ok(D)` if it has received one, and either
-    /// `Err(ReceiveError::Empty)` if it hasn't or
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or
     /// `Err(ReceiveError::Closed)` if the channel has been
     /// closed.
     pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
-        if let Some(ref mut receiver) = self.receiver {
-            match receiver.try_next() {
-                Ok(Some(data)) => Ok(data),
-                Ok(None) => {
-                    self.receiver = None;
-                    self.closed = true;
-                    Err(ReceiveError::Closed)
-                }
-                Err(_) => Err(ReceiveError::Empty),
-            }
-        } else {
-            Err(ReceiveError::Closed)
-        }
+        self.receiver.try_recv()
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel, resolving to `Ok(D)` once it has received
+    /// one, or either to `Err(ReceiveError::Disconnected)`
+    /// if the receiver has disconnected itself from the
+    /// channel or to `Err(ReceiveError::Closed)` if the
+    /// channel has been closed and its buffer is empty.
+    pub fn recv(&mut self) -> RecvFuture<'_, D> {
+        RecvFuture::new(self)
+    }
+
+    /// Receives a message over the channel (see [`recv`]),
+    /// blocking the current thread until there is one
+    /// available.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_blocking(&mut self) -> Result<D, ReceiveError> {
+        blocking::recv(self)
+    }
+
+    /// Receives a message over the channel (see
+    /// [`recv_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if no message has been
+    /// received in time.
+    ///
+    /// [`recv_blocking`]: #method.recv_blocking
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::recv_until(self, Instant::now() + timeout)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.recv_deadline(deadline, timer)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        RecvTimeoutFuture::new(self, deadline, timer)
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available (and, if the channel
+    /// is a rendezvous channel, letting the senders know
+    /// that the receiver is waiting for one).
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.receiver.poll_recv(cx)
+    }
+
+    /// Lets the senders know that the receiver isn't
+    /// waiting for a message anymore (if the channel is a
+    /// rendezvous channel), because the future or select
+    /// that was polling it has been dropped or resolved.
+    ///
+    /// If a message has already been handed off to the
+    /// receiver, it will still receive it.
+    pub(crate) fn stop_waiting(&mut self) {
+        self.receiver.stop_waiting();
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (closing it and dropping the messages still in its
+    /// buffer, since it is its only receiver), returning
+    /// `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        self.receiver.disconnect()
     }
 
     /// Tries to close the channel, returning `Ok(())` if it
-    /// succeeded, or `Err(CloseError::Closed)` if the channel
-    /// was already closed.
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
     pub fn close(&mut self) -> Result<(), CloseError> {
-        if self.closed {
-            Err(CloseError::Closed)
-        } else if let Some(ref mut receiver) = self.receiver {
-            receiver.close();
-            self.closed = true;
-            Ok(())
-        } else {
-            unreachable!();
-        }
+        self.receiver.close()
     }
 }
 
+impl<D> Unpin for Sender<D> {}
 impl<D> Unpin for Receiver<D> {}
 
+impl<D> PollSend<D> for Sender<D> {
+    fn poll_send(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>> {
+        self.sender.poll_send(cx, data)
+    }
+}
+
 impl<D> Sink<D> for Sender<D> {
-    // FIXME: -`()` +`D` (the issue being that `poll_ready`,
-    //   `poll_flush` and `poll_close` can't return `D` since
-    //   they don't get any data in the first place).
-    type SinkError = SendError<()>;
-
-    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).poll_ready(cx).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    // NOTE: if the channel's buffer is full (or if it is a
+    //   rendezvous channel), `start_send` keeps the message
+    //   until `poll_ready`, `poll_flush` or `poll_close`
+    //   manage to send it, or return it in an error if they
+    //   can't.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
     }
 
-    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<()>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).start_send(msg).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        self.get_mut().sender.start_send(msg)
     }
 
-    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).poll_flush(cx).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
     }
 
-    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).poll_close(cx).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_close(cx)
     }
 }
 
 impl<D> FusedStream for Receiver<D> {
     fn is_terminated(&self) -> bool {
-        self.closed
+        self.receiver.is_terminated()
     }
 }
 
 impl<D> Stream for Receiver<D> {
     type Item = D;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
-        if let Some(ref mut receiver) = self.receiver {
-            match Pin::new(receiver).poll_next(cx) {
-                Poll::Ready(None) => {
-                    self.receiver = None;
-                    self.closed = true;
-                    Poll::Ready(None)
-                }
-                poll => poll,
-            }
-        } else if self.closed {
-            Poll::Ready(None)
-        } else {
-            unreachable!();
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        match self.get_mut().poll_recv(cx) {
+            Poll::Ready(Ok(data)) => Poll::Ready(Some(data)),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
         }
     }
 }
 
 impl<D> Clone for Sender<D> {
+    /// # Panics
+    ///
+    /// This method panics if the channel already has the
+    /// maximum number of senders that it can have (see
+    /// [`try_clone`]).
+    ///
+    /// [`try_clone`]: #method.try_clone
     fn clone(&self) -> Sender<D> {
-        Sender {
-            sender: self.sender.clone(),
-            ..*self
-        }
+        self.try_clone()
+            .expect("the channel already has the maximum number of senders")
     }
 }
@@ -0,0 +1,555 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::stream::FusedStream;
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+
+use crate::blocking;
+use crate::error::*;
+use crate::future;
+use crate::timer::Timer;
+use crate::wakers::Wakers;
+
+/// Creates a new broadcast channel, which keeps the last
+/// `buf` messages that have been sent over it, and
+/// delivers a clone of every message to each of its
+/// receivers.
+///
+/// # Panics
+///
+/// This function panics if `buf` is `0`.
+pub fn new<D: Clone>(buf: usize) -> (Sender<D>, Receiver<D>) {
+    assert!(buf > 0, "a broadcast channel needs a buffer");
+
+    let inner = Arc::new(Inner::new(buf, D::clone));
+
+    (
+        Sender::new(buf, inner.clone()),
+        Receiver::new(buf, 0, inner),
+    )
+}
+
+#[derive(Debug)]
+/// The sending half of a broadcast channel, which shares
+/// the channel's state with the other senders and the
+/// receivers.
+pub struct Sender<D> {
+    /// The size of the buffer (as it was provided to
+    /// [`new`])
+    ///
+    /// [`new`]: fn.new.html
+    pub buf: usize,
+    /// Whether the sender has disconnected itself from the
+    /// channel.
+    disconnected: bool,
+    inner: Arc<Inner<D>>,
+}
+
+#[derive(Debug)]
+/// A receiving half of a broadcast channel, which shares
+/// the channel's state with the senders and the other
+/// receivers.
+///
+/// Every message sent over the channel is received by all
+/// the receivers, unless they lag behind by more than the
+/// size of the buffer.
+pub struct Receiver<D> {
+    /// The size of the buffer (as it was provided to
+    /// [`new`])
+    ///
+    /// [`new`]: fn.new.html
+    pub buf: usize,
+    /// The position of the next message that the receiver
+    /// will receive.
+    pos: usize,
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    disconnected: bool,
+    inner: Arc<Inner<D>>,
+}
+
+/// A future that receives a message over a broadcast
+/// channel (see [`Receiver::recv`]).
+///
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+pub type RecvFuture<'r, D> = future::RecvFuture<'r, Receiver<D>>;
+
+/// A future that receives a message over a broadcast
+/// channel, unless its deadline is reached first (see
+/// [`Receiver::recv_within`]).
+///
+/// [`Receiver::recv_within`]: struct.Receiver.html#method.recv_within
+pub type RecvTimeoutFuture<'r, D, T> = future::RecvTimeoutFuture<'r, Receiver<D>, T>;
+
+/// The state shared by the senders and the receivers of
+/// a broadcast channel.
+struct Inner<D> {
+    state: Mutex<State<D>>,
+    /// The function used to clone the messages for each
+    /// receiver.
+    clone: fn(&D) -> D,
+    /// The wakers of the receivers' tasks that are
+    /// waiting for a message.
+    wakers: Wakers,
+}
+
+struct State<D> {
+    /// The last messages that have been sent over the
+    /// channel.
+    msgs: VecDeque<D>,
+    /// The position of the first message of `msgs`.
+    head: usize,
+    /// Whether the channel has been closed.
+    closed: bool,
+    /// The number of connected senders.
+    senders: usize,
+    /// The number of connected receivers.
+    receivers: usize,
+}
+
+impl<D> Sender<D> {
+    fn new(buf: usize, inner: Arc<Inner<D>>) -> Sender<D> {
+        Sender {
+            buf,
+            disconnected: false,
+            inner,
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the senders or receivers).
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().closed
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Creates a new receiver, which will receive all the
+    /// messages sent over the channel from now on.
+    pub fn subscribe(&self) -> Receiver<D> {
+        let mut state = self.inner.lock();
+        state.receivers += 1;
+
+        Receiver::new(self.buf, state.tail(), self.inner.clone())
+    }
+
+    /// Sends `data` over the channel, dropping the oldest
+    /// message of the buffer if it is full, and returning
+    /// `Ok(())` if it has been successfully sent, or either
+    /// `Err(SendError::Disconnected)` if the  sender has
+    /// disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been closed.
+    ///
+    /// If the channel doesn't have any receiver, the
+    /// message is dropped (receivers that subscribe later
+    /// only receive the messages sent after they did).
+    pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
+        if self.disconnected {
+            return Err(SendError::Disconnected(data));
+        }
+
+        let mut state = self.inner.lock();
+        if state.closed {
+            return Err(SendError::Closed(data));
+        } else if state.receivers == 0 {
+            return Ok(());
+        }
+
+        if state.msgs.len() == self.buf {
+            state.msgs.pop_front();
+            state.head += 1;
+        }
+
+        state.msgs.push_back(data);
+        drop(state);
+
+        self.inner.wakers.wake_all();
+        Ok(())
+    }
+
+    /// Tries to disconnect the sender from the channel,
+    /// returning `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the sender
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.inner.remove_sender();
+            self.disconnected = true;
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the sender already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<D> Receiver<D> {
+    fn new(buf: usize, pos: usize, inner: Arc<Inner<D>>) -> Receiver<D> {
+        Receiver {
+            buf,
+            pos,
+            disconnected: false,
+            inner,
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the receivers or senders).
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().closed
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Tries to receive a message over the channel, returning
+    /// `Ok(D)` if it has received one, and either
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::Lagged(n))` if `n` messages have
+    /// been dropped before the receiver could receive them,
+    /// `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// closed.
+    ///
+    /// After returning `Err(ReceiveError::Lagged(n))`, the
+    /// receiver continues from the oldest message that is
+    /// still in the buffer.
+    pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
+        if self.disconnected {
+            return Err(ReceiveError::Disconnected);
+        }
+
+        let state = self.inner.lock();
+        if self.pos < state.head {
+            let lagged = state.head - self.pos;
+            self.pos = state.head;
+
+            return Err(ReceiveError::Lagged(lagged));
+        }
+
+        if let Some(data) = state.msgs.get(self.pos - state.head) {
+            let data = (self.inner.clone)(data);
+            self.pos += 1;
+            Ok(data)
+        } else if state.closed {
+            Err(ReceiveError::Closed)
+        } else {
+            Err(ReceiveError::Empty)
+        }
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel, resolving to `Ok(D)` once it has received
+    /// one, or either to `Err(ReceiveError::Lagged(n))` if
+    /// `n` messages have been dropped before the receiver
+    /// could receive them, to `Err(ReceiveError::Disconnected)`
+    /// if the receiver has disconnected itself from the
+    /// channel or to `Err(ReceiveError::Closed)` if the
+    /// channel has been closed and its buffer is empty.
+    pub fn recv(&mut self) -> RecvFuture<'_, D> {
+        RecvFuture::new(self)
+    }
+
+    /// Receives a message over the channel (see [`recv`]),
+    /// blocking the current thread until there is one
+    /// available.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_blocking(&mut self) -> Result<D, ReceiveError> {
+        blocking::recv(self)
+    }
+
+    /// Receives a message over the channel (see
+    /// [`recv_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if no message has been
+    /// received in time.
+    ///
+    /// [`recv_blocking`]: #method.recv_blocking
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::recv_until(self, Instant::now() + timeout)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.recv_deadline(deadline, timer)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        RecvTimeoutFuture::new(self, deadline, timer)
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        match self.try_recv() {
+            Err(ReceiveError::Empty) => (),
+            res => return Poll::Ready(res),
+        }
+
+        self.inner.wakers.register(cx.waker());
+
+        match self.try_recv() {
+            Err(ReceiveError::Empty) => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (the channel stays open even if it was the last
+    /// receiver, so that new ones can subscribe), returning
+    /// `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.inner.remove_receiver();
+            self.disconnected = true;
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<D> Inner<D> {
+    fn new(buf: usize, clone: fn(&D) -> D) -> Inner<D> {
+        Inner {
+            state: Mutex::new(State {
+                msgs: VecDeque::with_capacity(buf),
+                head: 0,
+                closed: false,
+                senders: 1,
+                receivers: 1,
+            }),
+            clone,
+            wakers: Wakers::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<D>> {
+        // NOTE: the state is only poisoned if cloning a
+        //   message panicked, which leaves it unchanged (and
+        //   the receivers and senders lock it when dropped,
+        //   which can happen while their thread is panicking).
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Closes the channel, returning whether it wasn't
+    /// already closed.
+    fn close(&self) -> bool {
+        let mut state = self.lock();
+        if state.closed {
+            return false;
+        }
+
+        state.closed = true;
+        drop(state);
+
+        self.wakers.wake_all();
+        true
+    }
+
+    /// Unregisters a sender, closing the channel if it
+    /// was the last one.
+    fn remove_sender(&self) {
+        let mut state = self.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.close();
+        }
+    }
+
+    /// Unregisters a receiver.
+    fn remove_receiver(&self) {
+        self.lock().receivers -= 1;
+    }
+}
+
+impl<D> State<D> {
+    /// The position of the next message that will be
+    /// sent over the channel.
+    fn tail(&self) -> usize {
+        self.head + self.msgs.len()
+    }
+}
+
+impl<D> Unpin for Receiver<D> {}
+
+impl<D> Sink<D> for Sender<D> {
+    // NOTE: the message is sent (or returned in an error)
+    //   by `start_send`, so `poll_ready` and `poll_flush`
+    //   never have to fail.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        self.get_mut().send(msg)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        let sender = self.get_mut();
+        if !sender.disconnected {
+            sender.inner.remove_sender();
+            sender.disconnected = true;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<D> FusedStream for Receiver<D> {
+    fn is_terminated(&self) -> bool {
+        if self.disconnected {
+            return true;
+        }
+
+        let state = self.inner.lock();
+        state.closed && self.pos >= state.tail()
+    }
+}
+
+impl<D> Stream for Receiver<D> {
+    // NOTE: the messages that the receiver missed because
+    //   it was lagging behind are skipped.
+    type Item = D;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        let receiver = self.get_mut();
+        loop {
+            match receiver.poll_recv(cx) {
+                Poll::Ready(Ok(data)) => return Poll::Ready(Some(data)),
+                Poll::Ready(Err(ReceiveError::Lagged(_))) => (),
+                Poll::Ready(Err(_)) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<D> Clone for Sender<D> {
+    fn clone(&self) -> Sender<D> {
+        if !self.disconnected {
+            self.inner.lock().senders += 1;
+        }
+
+        Sender {
+            buf: self.buf,
+            disconnected: self.disconnected,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<D> Drop for Sender<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_sender();
+        }
+    }
+}
+
+impl<D> Drop for Receiver<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_receiver();
+        }
+    }
+}
+
+impl<D> fmt::Debug for Inner<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("Inner")
+            .field("len", &state.msgs.len())
+            .field("head", &state.head)
+            .field("closed", &state.closed)
+            .field("senders", &state.senders)
+            .field("receivers", &state.receivers)
+            .finish()
+    }
+}
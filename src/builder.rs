@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use crate::bounded;
+use crate::channel::Receiver;
+use crate::channel::Sender;
+use crate::duplex::Endpoint;
+use crate::error::*;
+use crate::inner::Inner;
+use crate::mpmc;
+use crate::quota;
+use crate::unbounded;
+
+#[derive(Clone, Debug)]
+/// A builder of channels, which are defined by six
+/// properties:
+/// - whether they are uni- or bidirectional
+/// - whether their senders can get notified when a
+///   message they have sent has been read
+/// - the number of unread messages that they can hold
+/// - the total number of messages that can be sent over
+///   them
+/// - the number of senders that they can have
+/// - the number of receivers that they can have
+///
+/// By default, the builder creates unidirectional and
+/// unbounded channels that can have an unlimited number
+/// of senders but only one receiver.
+pub struct ChannelBuilder {
+    bidirectional: bool,
+    notify: bool,
+    buf: Option<usize>,
+    quota: Option<usize>,
+    max_senders: Option<usize>,
+    max_receivers: Option<usize>,
+}
+
+impl ChannelBuilder {
+    /// Creates a new builder with the default properties.
+    pub fn new() -> ChannelBuilder {
+        ChannelBuilder {
+            bidirectional: false,
+            notify: false,
+            buf: None,
+            quota: None,
+            max_senders: None,
+            max_receivers: Some(1),
+        }
+    }
+
+    /// Sets whether the channel is bidirectional (see
+    /// [`build_duplex`]).
+    ///
+    /// [`build_duplex`]: #method.build_duplex
+    pub fn bidirectional(mut self, bidirectional: bool) -> ChannelBuilder {
+        self.bidirectional = bidirectional;
+        self
+    }
+
+    /// Sets whether the senders can get notified when a
+    /// message they have sent has been read (see
+    /// [`Sender::try_send_notify`], which returns
+    /// `Err(SendError::Unsupported)` if they can't).
+    ///
+    /// [`Sender::try_send_notify`]: ../channel/enum.Sender.html#method.try_send_notify
+    pub fn notify(mut self, notify: bool) -> ChannelBuilder {
+        self.notify = notify;
+        self
+    }
+
+    /// Sets the number of unread messages that the
+    /// channel can hold (`None` if there is no limit).
+    ///
+    /// A channel that can't hold any message is a
+    /// rendezvous channel (see [`bounded::new`]).
+    ///
+    /// [`bounded::new`]: ../bounded/fn.new.html
+    pub fn buf(mut self, buf: Option<usize>) -> ChannelBuilder {
+        self.buf = buf;
+        self
+    }
+
+    /// Sets the total number of messages that can be
+    /// sent over the channel (`None` if there is no
+    /// limit).
+    pub fn quota(mut self, quota: Option<usize>) -> ChannelBuilder {
+        self.quota = quota;
+        self
+    }
+
+    /// Sets the maximum number of senders that the
+    /// channel can have at the same time (`None` if
+    /// there is no limit).
+    pub fn max_senders(mut self, max_senders: Option<usize>) -> ChannelBuilder {
+        self.max_senders = max_senders;
+        self
+    }
+
+    /// Sets the maximum number of receivers that the
+    /// channel can have at the same time (`None` if
+    /// there is no limit).
+    pub fn max_receivers(mut self, max_receivers: Option<usize>) -> ChannelBuilder {
+        self.max_receivers = max_receivers;
+        self
+    }
+
+    /// Builds a unidirectional channel with the builder's
+    /// properties, returning its sender and receiver, or
+    /// an error if the properties can't be combined.
+    pub fn build<D>(&self) -> Result<(Sender<D>, Receiver<D>), BuildError> {
+        self.check_limits()?;
+
+        if self.bidirectional {
+            return Err(BuildError::Direction);
+        }
+
+        let single = self.max_receivers == Some(1);
+
+        if let Some(quota) = self.quota {
+            if self.buf.is_some() || !single {
+                return Err(BuildError::Quota);
+            }
+
+            let inner = Inner::new(None)
+                .with_limits(self.max_senders, Some(1))
+                .with_notify(self.notify)
+                .with_quota(quota);
+            let inner = Arc::new(inner);
+
+            return Ok((
+                Sender::Quota(quota::Sender::new(quota, inner.clone())),
+                Receiver::Unbounded(unbounded::Receiver::new(inner)),
+            ));
+        }
+
+        if self.buf == Some(0) && !single {
+            return Err(BuildError::Receivers);
+        }
+
+        let inner = Inner::new(self.buf)
+            .with_limits(self.max_senders, self.max_receivers)
+            .with_notify(self.notify);
+        let inner = Arc::new(inner);
+
+        if !single {
+            Ok((
+                Sender::Mpmc(mpmc::Sender::new(inner.clone())),
+                Receiver::Mpmc(mpmc::Receiver::new(inner)),
+            ))
+        } else if let Some(buf) = self.buf {
+            Ok((
+                Sender::Bounded(bounded::Sender::new(buf, inner.clone())),
+                Receiver::Bounded(bounded::Receiver::new(buf, inner)),
+            ))
+        } else {
+            Ok((
+                Sender::Unbounded(unbounded::Sender::new(inner.clone())),
+                Receiver::Unbounded(unbounded::Receiver::new(inner)),
+            ))
+        }
+    }
+
+    /// Builds a bidirectional channel with the builder's
+    /// properties (each direction having its own buffer
+    /// and limits), returning its two endpoints, or an
+    /// error if the properties can't be combined.
+    #[allow(clippy::type_complexity)]
+    pub fn build_duplex<A, B>(&self) -> Result<(Endpoint<A, B>, Endpoint<B, A>), BuildError> {
+        self.check_limits()?;
+
+        if !self.bidirectional {
+            return Err(BuildError::Direction);
+        } else if self.quota.is_some() {
+            return Err(BuildError::Quota);
+        } else if self.notify {
+            return Err(BuildError::Notify);
+        } else if self.max_receivers != Some(1) {
+            return Err(BuildError::Receivers);
+        }
+
+        let buf = self.buf.ok_or(BuildError::Unbounded)?;
+
+        let (a_sender, a_receiver) = self.bounded(buf);
+        let (b_sender, b_receiver) = self.bounded(buf);
+
+        Ok((
+            Endpoint::new(a_sender, b_receiver),
+            Endpoint::new(b_sender, a_receiver),
+        ))
+    }
+
+    /// Checks that the channel can have at least one
+    /// sender and one receiver.
+    fn check_limits(&self) -> Result<(), BuildError> {
+        if self.max_senders == Some(0) || self.max_receivers == Some(0) {
+            Err(BuildError::NoSenderOrReceiver)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Creates a bounded channel which can hold `buf`
+    /// messages, with the builder's limit on the number
+    /// of senders.
+    fn bounded<D>(&self, buf: usize) -> (bounded::Sender<D>, bounded::Receiver<D>) {
+        match self.max_senders {
+            Some(max_senders) => bounded::with_max_senders(buf, max_senders),
+            None => bounded::new(buf),
+        }
+    }
+}
+
+impl Default for ChannelBuilder {
+    fn default() -> ChannelBuilder {
+        ChannelBuilder::new()
+    }
+}
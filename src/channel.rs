@@ -1,70 +1,281 @@
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 
 use futures_core::stream::FusedStream;
 use futures_core::stream::Stream;
 use futures_sink::Sink;
 
+use crate::blocking;
 use crate::bounded;
+use crate::broadcast;
 use crate::error::*;
+use crate::future;
+use crate::future::PollSend;
+use crate::mpmc;
+use crate::priority;
+use crate::quota;
+use crate::receipt::Receipt;
+use crate::timer::Timer;
 use crate::unbounded;
 
-/// Creates a new bounded channel (see [`futures-channel`'s
-/// documentation]).
+/// Creates a new bounded channel (see [`bounded::new`]).
 ///
-/// [`futures-channel`'s documentation]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/mpsc/fn.channel.html
+/// [`bounded::new`]: bounded/fn.new.html
 pub fn bounded<D>(buf: usize) -> (Sender<D>, Receiver<D>) {
     let (sender, receiver) = bounded::new(buf);
 
     (Sender::Bounded(sender), Receiver::Bounded(receiver))
 }
 
-/// Creates a new unbounded channel (see [`futures-channel`'s
-/// documentation]).
+/// Creates a new unbounded channel (see [`unbounded::new`]).
 ///
-/// [`futures-channel`'s documentation]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/mpsc/fn.unbounded.html
+/// [`unbounded::new`]: unbounded/fn.new.html
 pub fn unbounded<D>() -> (Sender<D>, Receiver<D>) {
     let (sender, receiver) = unbounded::new();
 
     (Sender::Unbounded(sender), Receiver::Unbounded(receiver))
 }
 
+/// Creates a new priority channel (see [`priority::new`]).
+///
+/// [`priority::new`]: priority/fn.new.html
+pub fn priority<D>(caps: &[usize]) -> (Sender<D>, Receiver<D>) {
+    let (sender, receiver) = priority::new(caps);
+
+    (Sender::Priority(sender), Receiver::Priority(receiver))
+}
+
+/// Creates a new broadcast channel (see [`broadcast::new`]).
+///
+/// [`broadcast::new`]: broadcast/fn.new.html
+pub fn broadcast<D: Clone>(buf: usize) -> (Sender<D>, Receiver<D>) {
+    let (sender, receiver) = broadcast::new(buf);
+
+    (Sender::Broadcast(sender), Receiver::Broadcast(receiver))
+}
+
 #[derive(Debug)]
-/// A wrapper around either a [`bounded::Sender`] or a
-/// [`unbounded::Sender`] that allows to use all the
-/// methods and traits that they implement.
+/// A wrapper around either a [`bounded::Sender`], an
+/// [`unbounded::Sender`], a [`broadcast::Sender`], an
+/// [`mpmc::Sender`], a [`priority::Sender`] or a
+/// [`quota::Sender`] that allows to use all the methods
+/// and traits that they implement.
 ///
 /// [`bounded::Sender`]: bounded/struct.Sender.html
 /// [`unbounded::Sender`]: unbounded/struct.Sender.html
+/// [`broadcast::Sender`]: broadcast/struct.Sender.html
+/// [`mpmc::Sender`]: mpmc/struct.Sender.html
+/// [`priority::Sender`]: priority/struct.Sender.html
+/// [`quota::Sender`]: quota/struct.Sender.html
 pub enum Sender<D> {
     Bounded(bounded::Sender<D>),
     Unbounded(unbounded::Sender<D>),
+    Broadcast(broadcast::Sender<D>),
+    Mpmc(mpmc::Sender<D>),
+    Priority(priority::Sender<D>),
+    Quota(quota::Sender<D>),
 }
 
+/// A future that sends data over a channel once there
+/// is a free slot in its buffer (see [`Sender::send`]).
+///
+/// [`Sender::send`]: enum.Sender.html#method.send
+pub type SendFuture<'s, D> = future::SendFuture<'s, Sender<D>, D>;
+
 #[derive(Debug)]
-/// A wrapper around either a [`bounded::Receiver`] or
-/// a [`unbounded::Receiver`] that allows to use all
-/// the methods and trait that they implement.
+/// A wrapper around either a [`bounded::Receiver`], an
+/// [`unbounded::Receiver`] (which is also the receiver of
+/// channels with a quota), a [`broadcast::Receiver`], an
+/// [`mpmc::Receiver`] or a [`priority::Receiver`] that
+/// allows to use all the methods and trait that they
+/// implement.
 ///
 /// [`bounded::Receiver`]: bounded/struct.Receiver.html
 /// [`unbounded::Receiver`]: unbounded/struct.Receiver.html
+/// [`broadcast::Receiver`]: broadcast/struct.Receiver.html
+/// [`mpmc::Receiver`]: mpmc/struct.Receiver.html
+/// [`priority::Receiver`]: priority/struct.Receiver.html
 pub enum Receiver<D> {
     Bounded(bounded::Receiver<D>),
     Unbounded(unbounded::Receiver<D>),
+    Broadcast(broadcast::Receiver<D>),
+    Mpmc(mpmc::Receiver<D>),
+    Priority(priority::Receiver<D>),
 }
 
+/// A future that receives a message over a channel (see
+/// [`Receiver::recv`]).
+///
+/// [`Receiver::recv`]: enum.Receiver.html#method.recv
+pub type RecvFuture<'r, D> = future::RecvFuture<'r, Receiver<D>>;
+
+/// A future that sends data over a channel once there
+/// is a free slot in its buffer, unless its deadline
+/// is reached first (see [`Sender::send_within`]).
+///
+/// [`Sender::send_within`]: enum.Sender.html#method.send_within
+pub type SendTimeoutFuture<'s, D, T> = future::SendTimeoutFuture<'s, Sender<D>, D, T>;
+
+/// A future that receives a message over a channel,
+/// unless its deadline is reached first (see
+/// [`Receiver::recv_within`]).
+///
+/// [`Receiver::recv_within`]: enum.Receiver.html#method.recv_within
+pub type RecvTimeoutFuture<'r, D, T> = future::RecvTimeoutFuture<'r, Receiver<D>, T>;
+
 impl<D> Sender<D> {
-    /// Sends `data` over the channel, returning `Ok(())` if
-    /// it has been successfully sent, or either
+    /// Whether the channel has been closed (by any of
+    /// the senders or receivers).
+    pub fn is_closed(&self) -> bool {
+        match self {
+            Sender::Bounded(sender) => sender.is_closed(),
+            Sender::Unbounded(sender) => sender.is_closed(),
+            Sender::Broadcast(sender) => sender.is_closed(),
+            Sender::Mpmc(sender) => sender.is_closed(),
+            Sender::Priority(sender) => sender.is_closed(),
+            Sender::Quota(sender) => sender.is_closed(),
+        }
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            Sender::Bounded(sender) => sender.is_disconnected(),
+            Sender::Unbounded(sender) => sender.is_disconnected(),
+            Sender::Broadcast(sender) => sender.is_disconnected(),
+            Sender::Mpmc(sender) => sender.is_disconnected(),
+            Sender::Priority(sender) => sender.is_disconnected(),
+            Sender::Quota(sender) => sender.is_disconnected(),
+        }
+    }
+
+    /// Tries to send `data` over the channel, returning
+    /// `Ok(())` if it has been successfully sent, or either
     /// `Err(SendError::Full)` if the channel's buffer is full,
     /// `Err(SendError::Disconnected)` if the  sender has
     /// disconnected itself from the channel or
     /// `Err(SendError::Closed)` if the channel has been closed.
-    pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
+    pub fn try_send(&mut self, data: D) -> Result<(), SendError<D>> {
         match self {
-            Sender::Bounded(sender) => sender.send(data),
+            Sender::Bounded(sender) => sender.try_send(data),
             Sender::Unbounded(sender) => sender.send(data),
+            Sender::Broadcast(sender) => sender.send(data),
+            Sender::Mpmc(sender) => sender.try_send(data),
+            Sender::Priority(sender) => sender.try_send(data),
+            Sender::Quota(sender) => sender.send(data),
+        }
+    }
+
+    /// Tries to send `data` over the channel (see
+    /// [`try_send`]), returning a [`Receipt`] that resolves
+    /// once it has been received if it has been successfully
+    /// sent.
+    ///
+    /// If the sender is a [`broadcast::Sender`], this
+    /// method returns `Err(SendError::Unsupported)`, since
+    /// messages sent over a broadcast channel don't have a
+    /// single reader. It does too if the channel has been
+    /// built without read notifications (see
+    /// [`ChannelBuilder::notify`]).
+    ///
+    /// [`try_send`]: #method.try_send
+    /// [`Receipt`]: receipt/struct.Receipt.html
+    /// [`broadcast::Sender`]: broadcast/struct.Sender.html
+    /// [`ChannelBuilder::notify`]: ../builder/struct.ChannelBuilder.html#method.notify
+    pub fn try_send_notify(&mut self, data: D) -> Result<Receipt, SendError<D>> {
+        match self {
+            Sender::Bounded(sender) => sender.try_send_notify(data),
+            Sender::Unbounded(sender) => sender.send_notify(data),
+            Sender::Broadcast(_) => Err(SendError::Unsupported(data)),
+            Sender::Mpmc(sender) => sender.try_send_notify(data),
+            Sender::Priority(sender) => sender.try_send_notify(data),
+            Sender::Quota(sender) => sender.send_notify(data),
+        }
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// once there is a free slot in its buffer, resolving
+    /// to `Ok(())` if it has been successfully sent, or
+    /// either `Err(SendError::Disconnected)` if the sender
+    /// has disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been
+    /// closed in the meantime.
+    pub fn send(&mut self, data: D) -> SendFuture<'_, D> {
+        SendFuture::new(self, data)
+    }
+
+    /// Sends `data` over the channel (see [`send`]),
+    /// blocking the current thread until there is a free
+    /// slot in the channel's buffer (if it is bounded).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_blocking(&mut self, data: D) -> Result<(), SendError<D>> {
+        blocking::send(self, data)
+    }
+
+    /// Sends `data` over the channel (see
+    /// [`send_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(SendError::Timeout)` if it couldn't be sent in
+    /// time.
+    ///
+    /// If the channel is a rendezvous channel and the data
+    /// has been handed off to the receiver before `timeout`
+    /// elapsed, the thread keeps blocking until the receiver
+    /// takes it.
+    ///
+    /// [`send_blocking`]: #method.send_blocking
+    pub fn send_timeout(&mut self, data: D, timeout: Duration) -> Result<(), SendError<D>> {
+        blocking::send_until(self, data, Instant::now() + timeout)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// (see [`send`]), or resolves to
+    /// `Err(SendError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_within<T: Timer>(
+        &mut self,
+        data: D,
+        timeout: Duration,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.send_deadline(data, deadline, timer)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// (see [`send`]), or resolves to
+    /// `Err(SendError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_deadline<T: Timer>(
+        &mut self,
+        data: D,
+        deadline: Instant,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        SendTimeoutFuture::new(self, data, deadline, timer)
+    }
+
+    /// Tries to clone the sender, returning the new sender if
+    /// it succeeded, or `Err(CloneError::Limit)` if the
+    /// channel already has the maximum number of senders
+    /// that it can have.
+    pub fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        match self {
+            Sender::Bounded(sender) => sender.try_clone().map(Sender::Bounded),
+            Sender::Unbounded(sender) => sender.try_clone().map(Sender::Unbounded),
+            Sender::Broadcast(sender) => Ok(Sender::Broadcast(sender.clone())),
+            Sender::Mpmc(sender) => sender.try_clone().map(Sender::Mpmc),
+            Sender::Priority(sender) => sender.try_clone().map(Sender::Priority),
+            Sender::Quota(sender) => sender.try_clone().map(Sender::Quota),
         }
     }
 
@@ -78,6 +289,10 @@ impl<D> Sender<D> {
         match self {
             Sender::Bounded(sender) => sender.disconnect(),
             Sender::Unbounded(sender) => sender.disconnect(),
+            Sender::Broadcast(sender) => sender.disconnect(),
+            Sender::Mpmc(sender) => sender.disconnect(),
+            Sender::Priority(sender) => sender.disconnect(),
+            Sender::Quota(sender) => sender.disconnect(),
         }
     }
 
@@ -90,67 +305,256 @@ impl<D> Sender<D> {
         match self {
             Sender::Bounded(sender) => sender.close(),
             Sender::Unbounded(sender) => sender.close(),
+            Sender::Broadcast(sender) => sender.close(),
+            Sender::Mpmc(sender) => sender.close(),
+            Sender::Priority(sender) => sender.close(),
+            Sender::Quota(sender) => sender.close(),
         }
     }
 }
 
 impl<D> Receiver<D> {
+    /// Whether the channel has been closed (by any of
+    /// the receivers or senders).
+    pub fn is_closed(&self) -> bool {
+        match self {
+            Receiver::Bounded(receiver) => receiver.is_closed(),
+            Receiver::Unbounded(receiver) => receiver.is_closed(),
+            Receiver::Broadcast(receiver) => receiver.is_closed(),
+            Receiver::Mpmc(receiver) => receiver.is_closed(),
+            Receiver::Priority(receiver) => receiver.is_closed(),
+        }
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            Receiver::Bounded(receiver) => receiver.is_disconnected(),
+            Receiver::Unbounded(receiver) => receiver.is_disconnected(),
+            Receiver::Broadcast(receiver) => receiver.is_disconnected(),
+            Receiver::Mpmc(receiver) => receiver.is_disconnected(),
+            Receiver::Priority(receiver) => receiver.is_disconnected(),
+        }
+    }
+
     /// Tries to receive a message over the channel, returning
     /// `Ok(D)` if it has received one, and either
-    /// `Err(ReceiveError::Empty)` if it hasn't or
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::Lagged(n))` if `n` messages have
+    /// been dropped before the receiver could receive them
+    /// (for broadcast channels),
+    /// `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or
     /// `Err(ReceiveError::Closed)` if the channel has been
     /// closed.
     pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
         match self {
             Receiver::Bounded(receiver) => receiver.try_recv(),
             Receiver::Unbounded(receiver) => receiver.try_recv(),
+            Receiver::Broadcast(receiver) => receiver.try_recv(),
+            Receiver::Mpmc(receiver) => receiver.try_recv(),
+            Receiver::Priority(receiver) => receiver.try_recv(),
+        }
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel, resolving to `Ok(D)` once it has received
+    /// one, or either to `Err(ReceiveError::Disconnected)`
+    /// if the receiver has disconnected itself from the
+    /// channel or to `Err(ReceiveError::Closed)` if the
+    /// channel has been closed and its buffer is empty.
+    pub fn recv(&mut self) -> RecvFuture<'_, D> {
+        RecvFuture::new(self)
+    }
+
+    /// Receives a message over the channel (see [`recv`]),
+    /// blocking the current thread until there is one
+    /// available.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_blocking(&mut self) -> Result<D, ReceiveError> {
+        blocking::recv(self)
+    }
+
+    /// Receives a message over the channel (see
+    /// [`recv_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if no message has been
+    /// received in time.
+    ///
+    /// [`recv_blocking`]: #method.recv_blocking
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::recv_until(self, Instant::now() + timeout)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.recv_deadline(deadline, timer)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        RecvTimeoutFuture::new(self, deadline, timer)
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        match self {
+            Receiver::Bounded(receiver) => receiver.poll_recv(cx),
+            Receiver::Unbounded(receiver) => receiver.poll_recv(cx),
+            Receiver::Broadcast(receiver) => receiver.poll_recv(cx),
+            Receiver::Mpmc(receiver) => receiver.poll_recv(cx),
+            Receiver::Priority(receiver) => receiver.poll_recv(cx),
+        }
+    }
+
+    /// Lets the senders know that the receiver isn't
+    /// waiting for a message anymore (see
+    /// [`bounded::Receiver::stop_waiting`]).
+    ///
+    /// [`bounded::Receiver::stop_waiting`]: bounded/struct.Receiver.html#method.stop_waiting
+    pub(crate) fn stop_waiting(&mut self) {
+        if let Receiver::Bounded(receiver) = self {
+            receiver.stop_waiting();
+        }
+    }
+
+    /// Tries to clone the receiver, returning the new
+    /// receiver if it succeeded, or `Err(CloneError::Limit)`
+    /// if the channel already has the maximum number of
+    /// receivers that it can have (which is always the case
+    /// for single-consumer channels).
+    ///
+    /// Broadcast receivers can't be cloned, and new ones
+    /// should be created using [`broadcast::Sender::subscribe`].
+    ///
+    /// [`broadcast::Sender::subscribe`]: ../broadcast/struct.Sender.html#method.subscribe
+    pub fn try_clone(&self) -> Result<Receiver<D>, CloneError> {
+        match self {
+            Receiver::Mpmc(receiver) => receiver.try_clone().map(Receiver::Mpmc),
+            _ => Err(CloneError::Limit),
+        }
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (closing it if it was the last receiver, unless it
+    /// is a broadcast channel), returning `Ok(())` if it
+    /// succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        match self {
+            Receiver::Bounded(receiver) => receiver.disconnect(),
+            Receiver::Unbounded(receiver) => receiver.disconnect(),
+            Receiver::Broadcast(receiver) => receiver.disconnect(),
+            Receiver::Mpmc(receiver) => receiver.disconnect(),
+            Receiver::Priority(receiver) => receiver.disconnect(),
         }
     }
 
     /// Tries to close the channel, returning `Ok(())` if it
-    /// succeeded, or `Err(CloseError::Closed)` if the channel
-    /// was already closed.
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
     pub fn close(&mut self) -> Result<(), CloseError> {
         match self {
             Receiver::Bounded(receiver) => receiver.close(),
             Receiver::Unbounded(receiver) => receiver.close(),
+            Receiver::Broadcast(receiver) => receiver.close(),
+            Receiver::Mpmc(receiver) => receiver.close(),
+            Receiver::Priority(receiver) => receiver.close(),
         }
     }
 }
 
 impl<D> Unpin for Receiver<D> {}
 
+impl<D> PollSend<D> for Sender<D> {
+    fn poll_send(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>> {
+        match self {
+            Sender::Bounded(sender) => sender.poll_send(cx, data),
+            Sender::Unbounded(sender) => Poll::Ready(sender.send(data.take().unwrap())),
+            Sender::Broadcast(sender) => Poll::Ready(sender.send(data.take().unwrap())),
+            Sender::Mpmc(sender) => sender.poll_send(cx, data),
+            Sender::Priority(sender) => sender.poll_send(cx, data),
+            Sender::Quota(sender) => Poll::Ready(sender.send(data.take().unwrap())),
+        }
+    }
+}
+
 impl<D> Sink<D> for Sender<D> {
-    // FIXME: -`()` +`D` (the issue being that `poll_ready`,
-    //   `poll_flush` and `poll_close` can't return `D` since
-    //   they don't get any data in the first place).
-    type SinkError = SendError<()>;
+    type SinkError = SendError<D>;
 
-    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
         match self.get_mut() {
             Sender::Bounded(sender) => Pin::new(sender).poll_ready(cx),
             Sender::Unbounded(sender) => Pin::new(sender).poll_ready(cx),
+            Sender::Broadcast(sender) => Pin::new(sender).poll_ready(cx),
+            Sender::Mpmc(sender) => Pin::new(sender).poll_ready(cx),
+            Sender::Priority(sender) => Pin::new(sender).poll_ready(cx),
+            Sender::Quota(sender) => Pin::new(sender).poll_ready(cx),
         }
     }
 
-    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<()>> {
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
         match self.get_mut() {
             Sender::Bounded(sender) => Pin::new(sender).start_send(msg),
             Sender::Unbounded(sender) => Pin::new(sender).start_send(msg),
+            Sender::Broadcast(sender) => Pin::new(sender).start_send(msg),
+            Sender::Mpmc(sender) => Pin::new(sender).start_send(msg),
+            Sender::Priority(sender) => Pin::new(sender).start_send(msg),
+            Sender::Quota(sender) => Pin::new(sender).start_send(msg),
         }
     }
 
-    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
         match self.get_mut() {
             Sender::Bounded(sender) => Pin::new(sender).poll_flush(cx),
             Sender::Unbounded(sender) => Pin::new(sender).poll_flush(cx),
+            Sender::Broadcast(sender) => Pin::new(sender).poll_flush(cx),
+            Sender::Mpmc(sender) => Pin::new(sender).poll_flush(cx),
+            Sender::Priority(sender) => Pin::new(sender).poll_flush(cx),
+            Sender::Quota(sender) => Pin::new(sender).poll_flush(cx),
         }
     }
 
-    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
         match self.get_mut() {
             Sender::Bounded(sender) => Pin::new(sender).poll_close(cx),
             Sender::Unbounded(sender) => Pin::new(sender).poll_close(cx),
+            Sender::Broadcast(sender) => Pin::new(sender).poll_close(cx),
+            Sender::Mpmc(sender) => Pin::new(sender).poll_close(cx),
+            Sender::Priority(sender) => Pin::new(sender).poll_close(cx),
+            Sender::Quota(sender) => Pin::new(sender).poll_close(cx),
         }
     }
 }
@@ -160,6 +564,9 @@ impl<D> FusedStream for Receiver<D> {
         match self {
             Receiver::Bounded(receiver) => receiver.is_terminated(),
             Receiver::Unbounded(receiver) => receiver.is_terminated(),
+            Receiver::Broadcast(receiver) => receiver.is_terminated(),
+            Receiver::Mpmc(receiver) => receiver.is_terminated(),
+            Receiver::Priority(receiver) => receiver.is_terminated(),
         }
     }
 }
@@ -171,15 +578,23 @@ impl<D> Stream for Receiver<D> {
         match self.get_mut() {
             Receiver::Bounded(receiver) => Pin::new(receiver).poll_next(cx),
             Receiver::Unbounded(receiver) => Pin::new(receiver).poll_next(cx),
+            Receiver::Broadcast(receiver) => Pin::new(receiver).poll_next(cx),
+            Receiver::Mpmc(receiver) => Pin::new(receiver).poll_next(cx),
+            Receiver::Priority(receiver) => Pin::new(receiver).poll_next(cx),
         }
     }
 }
 
 impl<D> Clone for Sender<D> {
+    /// # Panics
+    ///
+    /// This method panics if the channel already has the
+    /// maximum number of senders that it can have (see
+    /// [`try_clone`]).
+    ///
+    /// [`try_clone`]: #method.try_clone
     fn clone(&self) -> Sender<D> {
-        match self {
-            Sender::Bounded(sender) => Sender::Bounded(sender.clone()),
-            Sender::Unbounded(sender) => Sender::Unbounded(sender.clone()),
-        }
+        self.try_clone()
+            .expect("the channel already has the maximum number of senders")
     }
 }
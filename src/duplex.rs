@@ -0,0 +1,237 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::stream::FusedStream;
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+
+use crate::bounded;
+use crate::error::*;
+use crate::timer::Timer;
+
+/// Creates a new bidirectional channel, returning its two
+/// endpoints.
+///
+/// The first endpoint can send up to `a_buf` messages to
+/// the second one without them being received, and the
+/// second one can send up to `b_buf` messages to the first
+/// one.
+pub fn new<A, B>(a_buf: usize, b_buf: usize) -> (Endpoint<A, B>, Endpoint<B, A>) {
+    let (a_sender, a_receiver) = bounded::new(a_buf);
+    let (b_sender, b_receiver) = bounded::new(b_buf);
+
+    (
+        Endpoint::new(a_sender, b_receiver),
+        Endpoint::new(b_sender, a_receiver),
+    )
+}
+
+#[derive(Debug)]
+/// One of the two endpoints of a bidirectional channel,
+/// sending messages of type `S` to the other endpoint and
+/// receiving messages of type `R` from it.
+///
+/// Closing (or dropping) an endpoint closes the channel
+/// in both directions.
+pub struct Endpoint<S, R> {
+    sender: bounded::Sender<S>,
+    receiver: bounded::Receiver<R>,
+}
+
+impl<S, R> Endpoint<S, R> {
+    pub(crate) fn new(
+        sender: bounded::Sender<S>,
+        receiver: bounded::Receiver<R>,
+    ) -> Endpoint<S, R> {
+        Endpoint { sender, receiver }
+    }
+
+    /// Whether the channel has been closed (by any of the
+    /// endpoints).
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed() || self.receiver.is_closed()
+    }
+
+    /// Tries to send `data` to the other endpoint, returning
+    /// `Ok(())` if it has been successfully sent, or either
+    /// `Err(SendError::Full)` if the channel's buffer is full
+    /// or `Err(SendError::Closed)` if the channel has been
+    /// closed.
+    pub fn try_send(&mut self, data: S) -> Result<(), SendError<S>> {
+        self.sender.try_send(data)
+    }
+
+    /// Returns a future that sends `data` to the other
+    /// endpoint once there is a free slot in the channel's
+    /// buffer (see [`bounded::Sender::send`]).
+    ///
+    /// [`bounded::Sender::send`]: ../bounded/struct.Sender.html#method.send
+    pub fn send(&mut self, data: S) -> bounded::SendFuture<'_, S> {
+        self.sender.send(data)
+    }
+
+    /// Sends `data` to the other endpoint, blocking the
+    /// current thread until there is a free slot in the
+    /// channel's buffer (see [`bounded::Sender::send_blocking`]).
+    ///
+    /// [`bounded::Sender::send_blocking`]: ../bounded/struct.Sender.html#method.send_blocking
+    pub fn send_blocking(&mut self, data: S) -> Result<(), SendError<S>> {
+        self.sender.send_blocking(data)
+    }
+
+    /// Sends `data` to the other endpoint, blocking the
+    /// current thread for at most `timeout` (see
+    /// [`bounded::Sender::send_timeout`]).
+    ///
+    /// [`bounded::Sender::send_timeout`]: ../bounded/struct.Sender.html#method.send_timeout
+    pub fn send_timeout(&mut self, data: S, timeout: Duration) -> Result<(), SendError<S>> {
+        self.sender.send_timeout(data, timeout)
+    }
+
+    /// Returns a future that sends `data` to the other
+    /// endpoint, unless `timeout` elapses first (see
+    /// [`bounded::Sender::send_within`]).
+    ///
+    /// [`bounded::Sender::send_within`]: ../bounded/struct.Sender.html#method.send_within
+    pub fn send_within<T: Timer>(
+        &mut self,
+        data: S,
+        timeout: Duration,
+        timer: T,
+    ) -> bounded::SendTimeoutFuture<'_, S, T> {
+        self.sender.send_within(data, timeout, timer)
+    }
+
+    /// Returns a future that sends `data` to the other
+    /// endpoint, unless `deadline` is reached first (see
+    /// [`bounded::Sender::send_deadline`]).
+    ///
+    /// [`bounded::Sender::send_deadline`]: ../bounded/struct.Sender.html#method.send_deadline
+    pub fn send_deadline<T: Timer>(
+        &mut self,
+        data: S,
+        deadline: Instant,
+        timer: T,
+    ) -> bounded::SendTimeoutFuture<'_, S, T> {
+        self.sender.send_deadline(data, deadline, timer)
+    }
+
+    /// Tries to receive a message from the other endpoint,
+    /// returning `Ok(R)` if it has received one, and either
+    /// `Err(ReceiveError::Empty)` if it hasn't or
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// closed.
+    pub fn try_recv(&mut self) -> Result<R, ReceiveError> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns a future that receives a message from the
+    /// other endpoint (see [`bounded::Receiver::recv`]).
+    ///
+    /// [`bounded::Receiver::recv`]: ../bounded/struct.Receiver.html#method.recv
+    pub fn recv(&mut self) -> bounded::RecvFuture<'_, R> {
+        self.receiver.recv()
+    }
+
+    /// Receives a message from the other endpoint, blocking
+    /// the current thread until there is one available (see
+    /// [`bounded::Receiver::recv_blocking`]).
+    ///
+    /// [`bounded::Receiver::recv_blocking`]: ../bounded/struct.Receiver.html#method.recv_blocking
+    pub fn recv_blocking(&mut self) -> Result<R, ReceiveError> {
+        self.receiver.recv_blocking()
+    }
+
+    /// Receives a message from the other endpoint, blocking
+    /// the current thread for at most `timeout` (see
+    /// [`bounded::Receiver::recv_timeout`]).
+    ///
+    /// [`bounded::Receiver::recv_timeout`]: ../bounded/struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<R, ReceiveError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Returns a future that receives a message from the
+    /// other endpoint, unless `timeout` elapses first (see
+    /// [`bounded::Receiver::recv_within`]).
+    ///
+    /// [`bounded::Receiver::recv_within`]: ../bounded/struct.Receiver.html#method.recv_within
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> bounded::RecvTimeoutFuture<'_, R, T> {
+        self.receiver.recv_within(timeout, timer)
+    }
+
+    /// Returns a future that receives a message from the
+    /// other endpoint, unless `deadline` is reached first
+    /// (see [`bounded::Receiver::recv_deadline`]).
+    ///
+    /// [`bounded::Receiver::recv_deadline`]: ../bounded/struct.Receiver.html#method.recv_deadline
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> bounded::RecvTimeoutFuture<'_, R, T> {
+        self.receiver.recv_deadline(deadline, timer)
+    }
+
+    /// Tries to close the channel in both directions,
+    /// returning `Ok(())` if it succeeded, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        let sender = self.sender.close();
+        let receiver = self.receiver.close();
+
+        if sender.is_ok() || receiver.is_ok() {
+            Ok(())
+        } else {
+            Err(CloseError::Closed)
+        }
+    }
+
+    /// Splits the endpoint into the sender and the
+    /// receiver it is made of.
+    pub fn split(self) -> (bounded::Sender<S>, bounded::Receiver<R>) {
+        (self.sender, self.receiver)
+    }
+}
+
+impl<S, R> Sink<S> for Endpoint<S, R> {
+    type SinkError = SendError<S>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<S>>> {
+        Pin::new(&mut self.get_mut().sender).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: S) -> Result<(), SendError<S>> {
+        Pin::new(&mut self.get_mut().sender).start_send(msg)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<S>>> {
+        Pin::new(&mut self.get_mut().sender).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<S>>> {
+        Pin::new(&mut self.get_mut().sender).poll_close(cx)
+    }
+}
+
+impl<S, R> FusedStream for Endpoint<S, R> {
+    fn is_terminated(&self) -> bool {
+        self.receiver.is_terminated()
+    }
+}
+
+impl<S, R> Stream for Endpoint<S, R> {
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        Pin::new(&mut self.get_mut().receiver).poll_next(cx)
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(PartialEq, Eq, Debug)]
 /// The error type that is returned by the channels'
 /// senders when failing to send data.
@@ -8,6 +10,16 @@ pub enum SendError<D> {
     /// because it previously disconnected itself from
     /// the channel.
     Disconnected(D),
+    /// Returned when the channel's quota of messages has
+    /// been exhausted.
+    Exhausted(D),
+    /// Returned when the sender couldn't send the data
+    /// before the deadline it was given.
+    Timeout(D),
+    /// Returned when the channel doesn't support the way
+    /// the sender tried to send the data (e.g. with a
+    /// read receipt over a broadcast channel).
+    Unsupported(D),
     /// Returned when the channel has been closed.
     Closed(D),
 }
@@ -18,10 +30,24 @@ pub enum SendError<D> {
 pub enum ReceiveError {
     /// Returned when the channel's buffer is empty.
     Empty,
+    /// Returned when the receiver lagged behind the
+    /// senders of a broadcast channel, and missed the
+    /// given number of messages.
+    Lagged(usize),
+    /// Returned when the receiver didn't receive data
+    /// before the deadline it was given.
+    Timeout,
     /// Returned when the receiver coudln't receive data
     /// because it previously disconnected itself from
     /// the channel.
     Disconnected,
+    /// Returned when the sender of a single use channel
+    /// has been dropped without sending data.
+    SenderDropped,
+    /// Returned when the sender of a single use channel
+    /// has been dropped without sending data while its
+    /// thread was panicking.
+    SenderPanicked,
     /// Returned when the channel has been closed and
     /// its buffer is empty.
     Closed,
@@ -53,6 +79,68 @@ pub enum CloseError {
     Closed,
 }
 
+#[derive(PartialEq, Eq, Debug)]
+/// The error type that is returned by the channels'
+/// senders and receivers when failing to clone
+/// themselves.
+pub enum CloneError {
+    /// Returned when the channel already has the
+    /// maximum number of senders/receivers that it
+    /// can have.
+    Limit,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+/// The error type that is returned by the futures
+/// returned by [`Ask`]'s methods when failing to get a
+/// reply to a request.
+///
+/// [`Ask`]: ask/trait.Ask.html
+pub enum AskError<Q> {
+    /// Returned when the request couldn't be sent, along
+    /// with the error returned by the sender (which gives
+    /// the request back).
+    Send(SendError<Q>),
+    /// Returned when the request's envelope has been
+    /// dropped (or its reply handle closed) without a
+    /// reply being sent, along with the error returned
+    /// by the reply channel.
+    Dropped(ReceiveError),
+    /// Returned when no reply has been received before
+    /// the deadline the request was given.
+    Timeout,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+/// The error type that is returned by [`ChannelBuilder`]
+/// when failing to build a channel because of an invalid
+/// combination of properties.
+///
+/// [`ChannelBuilder`]: ../builder/struct.ChannelBuilder.html
+pub enum BuildError {
+    /// Returned when the maximum number of senders or
+    /// receivers has been set to zero.
+    NoSenderOrReceiver,
+    /// Returned when trying to build a unidirectional
+    /// channel with a bidirectional builder, or the
+    /// other way around.
+    Direction,
+    /// Returned when the channel has a quota of messages
+    /// and also has a buffer size, multiple receivers or
+    /// is bidirectional.
+    Quota,
+    /// Returned when the channel is bidirectional and
+    /// doesn't have a buffer size.
+    Unbounded,
+    /// Returned when the channel can have multiple
+    /// receivers and is bidirectional or doesn't buffer
+    /// messages (i.e. is a rendezvous channel).
+    Receivers,
+    /// Returned when the channel is bidirectional and
+    /// read notifications have been requested.
+    Notify,
+}
+
 impl<D> SendError<D> {
     /// Returns a reference to the data that the
     /// sender was trying to send over the channel.
@@ -60,6 +148,9 @@ impl<D> SendError<D> {
         match self {
             SendError::Full(data) => data,
             SendError::Disconnected(data) => data,
+            SendError::Exhausted(data) => data,
+            SendError::Timeout(data) => data,
+            SendError::Unsupported(data) => data,
             SendError::Closed(data) => data,
         }
     }
@@ -71,6 +162,9 @@ impl<D> SendError<D> {
         match self {
             SendError::Full(data) => data,
             SendError::Disconnected(data) => data,
+            SendError::Exhausted(data) => data,
+            SendError::Timeout(data) => data,
+            SendError::Unsupported(data) => data,
             SendError::Closed(data) => data,
         }
     }
@@ -81,6 +175,9 @@ impl<D> SendError<D> {
         match self {
             SendError::Full(data) => data,
             SendError::Disconnected(data) => data,
+            SendError::Exhausted(data) => data,
+            SendError::Timeout(data) => data,
+            SendError::Unsupported(data) => data,
             SendError::Closed(data) => data,
         }
     }
@@ -94,6 +191,9 @@ impl<D> SendError<D> {
         match self {
             SendError::Full(data) => SendError::Full(op(data)),
             SendError::Disconnected(data) => SendError::Disconnected(op(data)),
+            SendError::Exhausted(data) => SendError::Exhausted(op(data)),
+            SendError::Timeout(data) => SendError::Timeout(op(data)),
+            SendError::Unsupported(data) => SendError::Unsupported(op(data)),
             SendError::Closed(data) => SendError::Closed(op(data)),
         }
     }
@@ -101,32 +201,39 @@ impl<D> SendError<D> {
     /// Whether the sender failed to send data
     /// because the channel's buffer was full.
     pub fn is_full(&self) -> bool {
-        if let SendError::Full(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, SendError::Full(_))
     }
 
     /// Whether the sender failed to send data
     /// because it already disconnected itself from
     /// the channel.
     pub fn is_disconnected(&self) -> bool {
-        if let SendError::Disconnected(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, SendError::Disconnected(_))
+    }
+
+    /// Whether the sender failed to send data
+    /// because the channel's quota of messages has
+    /// been exhausted.
+    pub fn is_exhausted(&self) -> bool {
+        matches!(self, SendError::Exhausted(_))
+    }
+
+    /// Whether the sender failed to send data
+    /// before its deadline.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, SendError::Timeout(_))
+    }
+
+    /// Whether the sender failed to send data
+    /// because the channel doesn't support it.
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self, SendError::Unsupported(_))
     }
 
     /// Whether the sender failed to send data
     /// because the channel has been closed.
     pub fn is_closed(&self) -> bool {
-        if let SendError::Closed(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, SendError::Closed(_))
     }
 }
 
@@ -138,6 +245,19 @@ impl ReceiveError {
         *self == ReceiveError::Empty
     }
 
+    /// Whether the receiver failed to receive
+    /// data because it lagged behind the channel's
+    /// senders.
+    pub fn is_lagged(&self) -> bool {
+        matches!(self, ReceiveError::Lagged(_))
+    }
+
+    /// Whether the receiver failed to receive
+    /// data before its deadline.
+    pub fn is_timeout(&self) -> bool {
+        *self == ReceiveError::Timeout
+    }
+
     /// Whether the receiver failed to receive
     /// data because it already disconnected itself
     /// from the channel.
@@ -145,6 +265,21 @@ impl ReceiveError {
         *self == ReceiveError::Disconnected
     }
 
+    /// Whether the receiver failed to receive
+    /// data because the channel's sender has been
+    /// dropped without sending any (whether or not its
+    /// thread was panicking).
+    pub fn is_sender_dropped(&self) -> bool {
+        *self == ReceiveError::SenderDropped || *self == ReceiveError::SenderPanicked
+    }
+
+    /// Whether the receiver failed to receive
+    /// data because the channel's sender has been
+    /// dropped while its thread was panicking.
+    pub fn is_sender_panicked(&self) -> bool {
+        *self == ReceiveError::SenderPanicked
+    }
+
     /// Whether the receiver failed to receive
     /// data because the channel has been closed
     /// and its buffer empty.
@@ -153,6 +288,25 @@ impl ReceiveError {
     }
 }
 
+impl<Q> AskError<Q> {
+    /// Whether the request couldn't be sent.
+    pub fn is_send(&self) -> bool {
+        matches!(self, AskError::Send(_))
+    }
+
+    /// Whether the request's envelope has been
+    /// dropped without a reply being sent.
+    pub fn is_dropped(&self) -> bool {
+        matches!(self, AskError::Dropped(_))
+    }
+
+    /// Whether no reply has been received before
+    /// the deadline.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, AskError::Timeout)
+    }
+}
+
 impl DisconnectError {
     /// Whether the sender/receiver failed to
     /// disconnect itself from the channel because
@@ -184,3 +338,32 @@ impl CloseError {
         *self == CloseError::Closed
     }
 }
+
+impl CloneError {
+    /// Whether the sender/receiver failed to
+    /// clone itself because the channel already
+    /// has the maximum number of senders/receivers
+    /// that it can have.
+    pub fn is_limit(&self) -> bool {
+        *self == CloneError::Limit
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            BuildError::NoSenderOrReceiver => "a channel needs at least one sender and one receiver",
+            BuildError::Direction => "the channel's direction doesn't match the one of the builder",
+            BuildError::Quota => {
+                "a channel with a quota of messages can't have a buffer size, multiple receivers or be bidirectional"
+            }
+            BuildError::Unbounded => "a bidirectional channel needs a buffer size",
+            BuildError::Receivers => "a bidirectional or rendezvous channel can't have multiple receivers",
+            BuildError::Notify => "a bidirectional channel doesn't support read notifications",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for BuildError {}
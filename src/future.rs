@@ -0,0 +1,195 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use futures_core::future::Future;
+
+use crate::error::*;
+use crate::select::Selectable;
+use crate::timer::Delay;
+use crate::timer::Timer;
+
+/// A sender that can be polled to send data, which the
+/// futures of this module send their data with.
+pub(crate) trait PollSend<D> {
+    /// Tries to send the data stored in `data`, taking it
+    /// if the sender doesn't have to wait for a slot to
+    /// be freed.
+    ///
+    /// The data can be taken while this still returns
+    /// `Poll::Pending`, if the sender has to wait for it
+    /// to be received (over a rendezvous channel), in
+    /// which case it can't be given back anymore.
+    fn poll_send(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>>;
+}
+
+#[derive(Debug)]
+/// A future that sends data over a channel once there
+/// is a free slot in its buffer (see e.g.
+/// [`bounded::Sender::send`]).
+///
+/// [`bounded::Sender::send`]: ../bounded/struct.Sender.html#method.send
+pub struct SendFuture<'s, S, D> {
+    sender: &'s mut S,
+    data: Option<D>,
+}
+
+#[derive(Debug)]
+/// A future that sends data over a channel once there
+/// is a free slot in its buffer, unless its deadline is
+/// reached first (see e.g.
+/// [`bounded::Sender::send_within`]).
+///
+/// [`bounded::Sender::send_within`]: ../bounded/struct.Sender.html#method.send_within
+pub struct SendTimeoutFuture<'s, S, D, T: Timer> {
+    sender: &'s mut S,
+    data: Option<D>,
+    delay: Delay<T>,
+}
+
+#[derive(Debug)]
+/// A future that receives a message over a channel (see
+/// e.g. [`bounded::Receiver::recv`]).
+///
+/// [`bounded::Receiver::recv`]: ../bounded/struct.Receiver.html#method.recv
+pub struct RecvFuture<'r, R: Selectable> {
+    receiver: &'r mut R,
+}
+
+#[derive(Debug)]
+/// A future that receives a message over a channel,
+/// unless its deadline is reached first (see e.g.
+/// [`bounded::Receiver::recv_within`]).
+///
+/// [`bounded::Receiver::recv_within`]: ../bounded/struct.Receiver.html#method.recv_within
+pub struct RecvTimeoutFuture<'r, R: Selectable, T: Timer> {
+    receiver: &'r mut R,
+    delay: Delay<T>,
+}
+
+impl<'s, S, D> SendFuture<'s, S, D> {
+    pub(crate) fn new(sender: &'s mut S, data: D) -> SendFuture<'s, S, D> {
+        SendFuture {
+            sender,
+            data: Some(data),
+        }
+    }
+}
+
+impl<'s, S, D, T: Timer> SendTimeoutFuture<'s, S, D, T> {
+    pub(crate) fn new(
+        sender: &'s mut S,
+        data: D,
+        deadline: Instant,
+        timer: T,
+    ) -> SendTimeoutFuture<'s, S, D, T> {
+        SendTimeoutFuture {
+            sender,
+            data: Some(data),
+            delay: Delay::new(deadline, timer),
+        }
+    }
+}
+
+impl<'r, R: Selectable> RecvFuture<'r, R> {
+    pub(crate) fn new(receiver: &'r mut R) -> RecvFuture<'r, R> {
+        RecvFuture { receiver }
+    }
+}
+
+impl<'r, R: Selectable, T: Timer> RecvTimeoutFuture<'r, R, T> {
+    pub(crate) fn new(
+        receiver: &'r mut R,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'r, R, T> {
+        RecvTimeoutFuture {
+            receiver,
+            delay: Delay::new(deadline, timer),
+        }
+    }
+}
+
+impl<S, D> Unpin for SendFuture<'_, S, D> {}
+impl<S, D, T: Timer> Unpin for SendTimeoutFuture<'_, S, D, T> {}
+impl<R: Selectable> Unpin for RecvFuture<'_, R> {}
+impl<R: Selectable, T: Timer> Unpin for RecvTimeoutFuture<'_, R, T> {}
+
+impl<S: PollSend<D>, D> Future for SendFuture<'_, S, D> {
+    type Output = Result<(), SendError<D>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        let fut = self.get_mut();
+        fut.sender.poll_send(cx, &mut fut.data)
+    }
+}
+
+impl<S: PollSend<D>, D, T: Timer> Future for SendTimeoutFuture<'_, S, D, T> {
+    type Output = Result<(), SendError<D>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        let fut = self.get_mut();
+        match fut.sender.poll_send(cx, &mut fut.data) {
+            Poll::Pending => (),
+            poll => return poll,
+        }
+
+        // NOTE: once the message has been handed off to the
+        //   receiver of a rendezvous channel, it can't be given
+        //   back anymore, so the future waits for the receiver
+        //   to take it.
+        if fut.data.is_none() {
+            return Poll::Pending;
+        }
+
+        match fut.delay.poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(SendError::Timeout(fut.data.take().unwrap()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<R: Selectable> Future for RecvFuture<'_, R> {
+    type Output = Result<R::Item, ReceiveError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<R::Item, ReceiveError>> {
+        self.get_mut().receiver.poll_select(cx)
+    }
+}
+
+impl<R: Selectable, T: Timer> Future for RecvTimeoutFuture<'_, R, T> {
+    type Output = Result<R::Item, ReceiveError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<R::Item, ReceiveError>> {
+        let fut = self.get_mut();
+        match fut.receiver.poll_select(cx) {
+            Poll::Pending => (),
+            poll => return poll,
+        }
+
+        match fut.delay.poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(ReceiveError::Timeout)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+// NOTE: the receiver stops waiting for a message once the
+//   future is dropped (see `Selectable::abandon`), so that
+//   none is handed off to it over a rendezvous channel.
+impl<R: Selectable> Drop for RecvFuture<'_, R> {
+    fn drop(&mut self) {
+        self.receiver.abandon();
+    }
+}
+
+impl<R: Selectable, T: Timer> Drop for RecvTimeoutFuture<'_, R, T> {
+    fn drop(&mut self) {
+        self.receiver.abandon();
+    }
+}
@@ -0,0 +1,1137 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::task::Context;
+use std::task::Poll;
+use std::thread;
+
+use futures_core::future::Future;
+
+use crate::bounded::Overflow;
+use crate::error::*;
+use crate::queue::Link;
+use crate::queue::Linked;
+use crate::queue::Queue;
+use crate::receipt;
+use crate::receipt::Notifier;
+use crate::receipt::Receipt;
+use crate::wakers::Wakers;
+
+/// The state shared by the senders and the receivers of
+/// a bounded, unbounded or multi-consumer channel.
+pub(crate) struct Inner<D> {
+    queue: Queue<Message<D>>,
+    /// The number of messages in the queue, plus the
+    /// number of slots that have been reserved by the
+    /// senders.
+    len: AtomicUsize,
+    /// The size of the buffer (`None` if the channel is
+    /// unbounded).
+    buf: Option<usize>,
+    /// The number of receivers waiting for a message that
+    /// hasn't been handed off to them yet, which is the
+    /// number of messages that can be handed off if the
+    /// channel is a rendezvous channel (i.e. if `buf` is
+    /// `Some(0)`).
+    waiting: AtomicUsize,
+    /// The number of messages that can still be sent over
+    /// the channel (`None` if there is no limit).
+    quota: Option<AtomicUsize>,
+    /// Whether the channel has been closed.
+    closed: AtomicBool,
+    /// Whether the senders can ask for the read receipts
+    /// of their messages.
+    notify: bool,
+    /// The number of connected senders.
+    senders: AtomicUsize,
+    /// The maximum number of connected senders (`None`
+    /// if there is no limit).
+    max_senders: Option<usize>,
+    /// The number of connected receivers.
+    receivers: AtomicUsize,
+    /// The maximum number of connected receivers (`None`
+    /// if there is no limit).
+    max_receivers: Option<usize>,
+    /// The lock that receivers have to acquire before
+    /// popping messages if the channel can have more
+    /// than one receiver.
+    consumer: Mutex<()>,
+    /// What happens when a message is sent while the
+    /// buffer is full.
+    overflow: Overflow,
+    /// The number of messages that have been evicted
+    /// because the buffer was full.
+    evicted: AtomicUsize,
+    /// The function that the evicted messages are passed
+    /// to (if any).
+    on_evict: Option<Box<dyn Fn(D) + Send + Sync>>,
+    /// The wakers of the receivers' tasks that are
+    /// waiting for a message.
+    recv_wakers: Wakers,
+    /// The wakers of the senders' tasks that are
+    /// waiting for a free slot.
+    send_wakers: Wakers,
+}
+
+#[derive(Debug)]
+/// The state of a sending half of a bounded, unbounded or
+/// multi-consumer channel (or of a channel with a quota),
+/// which their senders wrap.
+pub(crate) struct Sender<D> {
+    /// Whether the sender has disconnected itself from the
+    /// channel.
+    disconnected: bool,
+    /// The message that the sender is waiting to send
+    /// (when used as a [`Sink`]).
+    ///
+    /// [`Sink`]: https://docs.rs/futures-sink-preview/0.3.0-alpha.16/futures_sink/trait.Sink.html
+    pending: Option<D>,
+    /// The receipt of the message that the sender has
+    /// handed off to the receiver, if it is waiting for
+    /// the receiver to take it (if the channel is a
+    /// rendezvous channel).
+    handoff: Option<Receipt>,
+    inner: Arc<Inner<D>>,
+}
+
+#[derive(Debug)]
+/// The state of a receiving half of a bounded, unbounded
+/// or multi-consumer channel, which their receivers wrap.
+pub(crate) struct Receiver<D> {
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    disconnected: bool,
+    /// Whether the receiver is waiting for a message to be
+    /// handed off to it, or hasn't received the message
+    /// that has been handed off to it yet (if the channel
+    /// is a rendezvous channel).
+    waiting: bool,
+    /// Whether the channel can have more than one
+    /// receiver, which then have to hold the consumer lock
+    /// to pop messages.
+    shared: bool,
+    inner: Arc<Inner<D>>,
+}
+
+/// A message stored in the channel's buffer, along with
+/// the notifier of its read receipt, if the sender asked
+/// for one.
+#[repr(C)]
+pub(crate) struct Message<D> {
+    link: Link,
+    pub(crate) data: D,
+    pub(crate) notifier: Option<Notifier>,
+}
+
+impl Overflow {
+    /// Whether messages are evicted when the buffer is
+    /// full.
+    fn evicts(self) -> bool {
+        self == Overflow::DropOldest || self == Overflow::DropNewest
+    }
+}
+
+impl<D> Inner<D> {
+    /// Creates the state of a new channel with one
+    /// sender and one receiver, which can hold exactly
+    /// `buf` messages (or an unlimited number of messages
+    /// if `buf` is `None`).
+    pub(crate) fn new(buf: Option<usize>) -> Inner<D> {
+        Inner {
+            queue: Queue::new(),
+            len: AtomicUsize::new(0),
+            buf,
+            waiting: AtomicUsize::new(0),
+            quota: None,
+            closed: AtomicBool::new(false),
+            notify: true,
+            senders: AtomicUsize::new(1),
+            max_senders: None,
+            receivers: AtomicUsize::new(1),
+            max_receivers: None,
+            consumer: Mutex::new(()),
+            overflow: Overflow::Block,
+            evicted: AtomicUsize::new(0),
+            on_evict: None,
+            recv_wakers: Wakers::new(),
+            send_wakers: Wakers::new(),
+        }
+    }
+
+    /// Limits the number of messages that can be sent
+    /// over the channel to `quota`, closing it if `quota`
+    /// is `0`.
+    pub(crate) fn with_quota(self, quota: usize) -> Inner<D> {
+        let inner = Inner {
+            quota: Some(AtomicUsize::new(quota)),
+            ..self
+        };
+
+        if quota == 0 {
+            inner.close();
+        }
+
+        inner
+    }
+
+    /// Limits the number of senders and receivers that
+    /// the channel can have at the same time to
+    /// `max_senders` and `max_receivers` (or to an
+    /// unlimited number if they are `None`).
+    pub(crate) fn with_limits(
+        self,
+        max_senders: Option<usize>,
+        max_receivers: Option<usize>,
+    ) -> Inner<D> {
+        assert!(
+            max_senders != Some(0) && max_receivers != Some(0),
+            "a channel needs at least one sender and one receiver",
+        );
+
+        Inner {
+            max_senders,
+            max_receivers,
+            ..self
+        }
+    }
+
+    /// Sets whether the senders can ask for the read
+    /// receipts of their messages.
+    pub(crate) fn with_notify(self, notify: bool) -> Inner<D> {
+        Inner { notify, ..self }
+    }
+
+    /// Makes the channel handle the messages sent while its
+    /// buffer is full as defined by `overflow`, passing the
+    /// messages it evicts to `on_evict` (if any).
+    pub(crate) fn with_overflow(
+        self,
+        overflow: Overflow,
+        on_evict: Option<Box<dyn Fn(D) + Send + Sync>>,
+    ) -> Inner<D> {
+        assert!(
+            !self.is_rendezvous() || !overflow.evicts(),
+            "a rendezvous channel can't evict messages",
+        );
+
+        Inner {
+            overflow,
+            on_evict,
+            ..self
+        }
+    }
+
+    /// What happens when a message is sent while the
+    /// buffer is full.
+    pub(crate) fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// The number of messages that have been evicted
+    /// because the buffer was full.
+    pub(crate) fn evicted(&self) -> usize {
+        self.evicted.load(Ordering::SeqCst)
+    }
+
+    /// Whether the receivers have to hold the lock returned
+    /// by [`lock_consumer`] to pop messages, even if the
+    /// channel can only have one receiver.
+    ///
+    /// [`lock_consumer`]: #method.lock_consumer
+    pub(crate) fn shares_consumer(&self) -> bool {
+        self.overflow == Overflow::DropOldest
+    }
+
+    /// Whether the channel has been closed.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Whether the channel has been closed and its
+    /// buffer is empty.
+    pub(crate) fn is_terminated(&self) -> bool {
+        self.is_closed() && self.len.load(Ordering::SeqCst) == 0
+    }
+
+    /// Closes the channel, returning whether it wasn't
+    /// already closed.
+    pub(crate) fn close(&self) -> bool {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        self.recv_wakers.wake_all();
+        self.send_wakers.wake_all();
+        true
+    }
+
+    /// Whether the channel is a rendezvous channel, which
+    /// doesn't buffer messages but hands them off to the
+    /// receivers that are waiting for one.
+    pub(crate) fn is_rendezvous(&self) -> bool {
+        self.buf == Some(0)
+    }
+
+    /// Registers a receiver that is waiting for a message,
+    /// allowing a sender to hand one off to it if the
+    /// channel is a rendezvous channel.
+    ///
+    /// The receiver stops waiting once a message has been
+    /// handed off to it (see [`try_reserve`]), or when it
+    /// calls [`stop_waiting`].
+    ///
+    /// [`try_reserve`]: #method.try_reserve
+    /// [`stop_waiting`]: #method.stop_waiting
+    pub(crate) fn start_waiting(&self) {
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        self.send_wakers.wake_all();
+    }
+
+    /// Unregisters a receiver that was waiting for a
+    /// message, returning `false` if a message has already
+    /// been handed off to it (in which case it is still
+    /// in the channel's buffer, waiting to be received).
+    pub(crate) fn stop_waiting(&self) -> bool {
+        try_decrement(&self.waiting)
+    }
+
+    /// Tries to register a new sender, returning whether
+    /// the channel didn't already have the maximum number
+    /// of senders.
+    pub(crate) fn try_add_sender(&self) -> bool {
+        try_increment(&self.senders, self.max_senders)
+    }
+
+    /// Unregisters a sender, closing the channel if it
+    /// was the last one.
+    pub(crate) fn remove_sender(&self) {
+        if self.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.close();
+        }
+    }
+
+    /// Tries to register a new receiver, returning whether
+    /// the channel didn't already have the maximum number
+    /// of receivers.
+    pub(crate) fn try_add_receiver(&self) -> bool {
+        try_increment(&self.receivers, self.max_receivers)
+    }
+
+    /// Unregisters a receiver, closing the channel and
+    /// returning `true` if it was the last one.
+    pub(crate) fn remove_receiver(&self) -> bool {
+        if self.receivers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.close();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of messages that can still be sent
+    /// over the channel (`None` if there is no limit).
+    pub(crate) fn remaining(&self) -> Option<usize> {
+        self.quota
+            .as_ref()
+            .map(|quota| quota.load(Ordering::SeqCst))
+    }
+
+    /// Tries to take a message from the channel's quota,
+    /// returning `None` if it is exhausted, or whether it
+    /// was the last one.
+    fn take_quota(&self) -> Option<bool> {
+        let quota = match self.quota {
+            Some(ref quota) => quota,
+            None => return Some(false),
+        };
+
+        let mut remaining = quota.load(Ordering::SeqCst);
+        loop {
+            if remaining == 0 {
+                return None;
+            }
+
+            match quota.compare_exchange_weak(
+                remaining,
+                remaining - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return Some(remaining == 1),
+                Err(actual) => remaining = actual,
+            }
+        }
+    }
+
+    /// Gives back a message that was taken from the
+    /// channel's quota but couldn't be sent.
+    fn give_back_quota(&self) {
+        if let Some(ref quota) = self.quota {
+            quota.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Acquires the lock that receivers have to hold to
+    /// pop messages if the channel can have more than
+    /// one receiver.
+    pub(crate) fn lock_consumer(&self) -> MutexGuard<'_, ()> {
+        self.consumer.lock().unwrap()
+    }
+
+    /// Tries to reserve a slot in the channel's buffer (or
+    /// one of the waiting receivers if the channel is a
+    /// rendezvous channel), returning whether it succeeded.
+    pub(crate) fn try_reserve(&self) -> bool {
+        if self.is_rendezvous() {
+            // NOTE: the receiver stops waiting as soon as the
+            //   message is handed off to it, so that another
+            //   message can't be handed off to it.
+            if !try_decrement(&self.waiting) {
+                return false;
+            }
+
+            self.len.fetch_add(1, Ordering::SeqCst);
+            true
+        } else {
+            try_increment(&self.len, self.buf)
+        }
+    }
+
+    /// Tries to reserve a slot in the channel's buffer,
+    /// registering the task to be woken up when a slot
+    /// is freed if there is none available, and returning
+    /// `Poll::Ready(false)` if the channel is closed.
+    pub(crate) fn poll_reserve(&self, cx: &mut Context) -> Poll<bool> {
+        if self.is_closed() {
+            return Poll::Ready(false);
+        } else if self.try_reserve() {
+            return Poll::Ready(true);
+        }
+
+        self.send_wakers.register(cx.waker());
+
+        if self.is_closed() {
+            Poll::Ready(false)
+        } else if self.try_reserve() {
+            Poll::Ready(true)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Releases a slot that was previously reserved.
+    pub(crate) fn release(&self) {
+        self.len.fetch_sub(1, Ordering::SeqCst);
+        self.send_wakers.wake_all();
+    }
+
+    /// Pushes `data` in the slot that was previously
+    /// reserved, along with the notifier of its read
+    /// receipt, if any.
+    pub(crate) fn push(&self, data: D, notifier: Option<Notifier>) {
+        let msg = Box::new(Message::new(data, notifier));
+        if self.max_senders == Some(1) {
+            // SAFETY: the channel can't have more than one
+            //   sender (and a disconnected sender can't
+            //   push messages).
+            unsafe { self.queue.push_single(msg) };
+        } else {
+            self.queue.push(msg);
+        }
+
+        self.recv_wakers.wake_all();
+    }
+
+    /// Tries to send `data` over the channel, along with
+    /// the notifier of its read receipt, if any, passing
+    /// the message evicted to make room for it (if any) to
+    /// the channel's eviction callback.
+    ///
+    /// If the channel has a quota, it is closed after its
+    /// last message has been sent.
+    ///
+    /// If the senders can't ask for read receipts and
+    /// `notifier` isn't `None`, this method returns
+    /// `Err(SendError::Unsupported)`.
+    pub(crate) fn try_send(&self, data: D, notifier: Option<Notifier>) -> Result<(), SendError<D>> {
+        if let Some(evicted) = self.try_send_evicting(data, notifier)? {
+            if let Some(ref on_evict) = self.on_evict {
+                on_evict(evicted);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tries to send `data` over the channel (see
+    /// [`try_send`]), returning the message that has been
+    /// evicted to make room for it (or `data` itself if the
+    /// channel drops the newest messages), if any.
+    ///
+    /// [`try_send`]: #method.try_send
+    pub(crate) fn try_send_evicting(
+        &self,
+        data: D,
+        notifier: Option<Notifier>,
+    ) -> Result<Option<D>, SendError<D>> {
+        if notifier.is_some() && !self.notify {
+            return Err(SendError::Unsupported(data));
+        }
+
+        let last = match self.take_quota() {
+            Some(last) => last,
+            None => return Err(SendError::Exhausted(data)),
+        };
+
+        loop {
+            if self.is_closed() {
+                self.give_back_quota();
+                return Err(SendError::Closed(data));
+            } else if self.try_reserve() {
+                break;
+            }
+
+            match self.overflow {
+                Overflow::Reject | Overflow::Block => {
+                    self.give_back_quota();
+                    return Err(SendError::Full(data));
+                }
+                Overflow::DropNewest => {
+                    self.evicted.fetch_add(1, Ordering::SeqCst);
+                    if last {
+                        self.close();
+                    }
+
+                    return Ok(Some(data));
+                }
+                Overflow::DropOldest => {
+                    let consumer = self.lock_consumer();
+                    // SAFETY: the receivers of a channel that drops
+                    //   its oldest messages hold the consumer lock
+                    //   to pop messages (see `shares_consumer`).
+                    if let Some(msg) = unsafe { self.queue.pop() } {
+                        // NOTE: the slot of the evicted message is
+                        //   reused instead of being released.
+                        self.evicted.fetch_add(1, Ordering::SeqCst);
+                        self.push(data, notifier);
+                        if last {
+                            self.close();
+                        }
+
+                        return Ok(Some(msg.data));
+                    }
+
+                    // NOTE: every slot has been reserved by a sender
+                    //   that hasn't pushed its message yet, so the
+                    //   sender lets them do it before retrying.
+                    drop(consumer);
+                    thread::yield_now();
+                }
+            }
+        }
+
+        self.push(data, notifier);
+        if last {
+            self.close();
+        }
+
+        Ok(None)
+    }
+
+    /// Tries to receive a message over the channel.
+    ///
+    /// # Safety
+    ///
+    /// There can only be one receiver popping messages
+    /// at a time (see [`lock_consumer`]).
+    ///
+    /// [`lock_consumer`]: #method.lock_consumer
+    pub(crate) unsafe fn try_recv(&self) -> Result<D, ReceiveError> {
+        let closed = self.is_closed();
+
+        if let Some(msg) = self.queue.pop() {
+            self.release();
+            Ok(msg.received())
+        } else if closed {
+            Err(ReceiveError::Closed)
+        } else {
+            Err(ReceiveError::Empty)
+        }
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one
+    /// is sent if the buffer is empty.
+    ///
+    /// # Safety
+    ///
+    /// See [`try_recv`].
+    ///
+    /// [`try_recv`]: #method.try_recv
+    pub(crate) unsafe fn poll_recv(&self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        match self.try_recv() {
+            Err(ReceiveError::Empty) => (),
+            res => return Poll::Ready(res),
+        }
+
+        self.recv_wakers.register(cx.waker());
+
+        match self.try_recv() {
+            Err(ReceiveError::Empty) => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+
+    /// Drops all the messages that are still in the
+    /// channel's buffer (and thus notifies their read
+    /// receipts that they won't be received).
+    ///
+    /// # Safety
+    ///
+    /// See [`try_recv`].
+    ///
+    /// [`try_recv`]: #method.try_recv
+    pub(crate) unsafe fn drain(&self) {
+        while self.queue.pop().is_some() {
+            self.release();
+        }
+    }
+}
+
+impl<D> Sender<D> {
+    pub(crate) fn new(inner: Arc<Inner<D>>) -> Sender<D> {
+        Sender {
+            disconnected: false,
+            pending: None,
+            handoff: None,
+            inner,
+        }
+    }
+
+    /// The state shared by the senders and the receivers
+    /// of the channel.
+    pub(crate) fn inner(&self) -> &Inner<D> {
+        &self.inner
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Tries to send `data` over the channel (see
+    /// [`bounded::Sender::try_send`]).
+    ///
+    /// [`bounded::Sender::try_send`]: ../bounded/struct.Sender.html#method.try_send
+    pub(crate) fn try_send(&self, data: D) -> Result<(), SendError<D>> {
+        if self.disconnected {
+            Err(SendError::Disconnected(data))
+        } else {
+            self.inner.try_send(data, None)
+        }
+    }
+
+    /// Tries to send `data` over the channel, returning the
+    /// message that has been evicted to make room for it
+    /// (see [`bounded::Sender::try_send_evicting`]).
+    ///
+    /// [`bounded::Sender::try_send_evicting`]: ../bounded/struct.Sender.html#method.try_send_evicting
+    pub(crate) fn try_send_evicting(&self, data: D) -> Result<Option<D>, SendError<D>> {
+        if self.disconnected {
+            Err(SendError::Disconnected(data))
+        } else {
+            self.inner.try_send_evicting(data, None)
+        }
+    }
+
+    /// Tries to send `data` over the channel, returning a
+    /// [`Receipt`] that resolves once it has been received
+    /// (see [`bounded::Sender::try_send_notify`]).
+    ///
+    /// [`Receipt`]: ../receipt/struct.Receipt.html
+    /// [`bounded::Sender::try_send_notify`]: ../bounded/struct.Sender.html#method.try_send_notify
+    pub(crate) fn try_send_notify(&self, data: D) -> Result<Receipt, SendError<D>> {
+        if self.disconnected {
+            return Err(SendError::Disconnected(data));
+        }
+
+        let (notifier, receipt) = receipt::new();
+        self.inner.try_send(data, Some(notifier))?;
+
+        Ok(receipt)
+    }
+
+    /// Tries to send the data stored in `data`, taking it
+    /// if the sender doesn't have to wait for a slot to
+    /// be freed (see [`PollSend::poll_send`]).
+    ///
+    /// If the channel is a rendezvous channel, the data is
+    /// taken once it has been handed off to the receiver,
+    /// but this keeps returning `Poll::Pending` until the
+    /// receiver has taken it.
+    ///
+    /// [`PollSend::poll_send`]: ../future/trait.PollSend.html#tymethod.poll_send
+    pub(crate) fn poll_send(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>> {
+        if data.is_none() {
+            return self.poll_handoff(cx);
+        }
+
+        // NOTE: the receipt of a message handed off by a send
+        //   that has been dropped since isn't waited for.
+        self.handoff = None;
+
+        if self.disconnected {
+            return Poll::Ready(Err(SendError::Disconnected(data.take().unwrap())));
+        } else if self.inner.overflow() != Overflow::Block {
+            return Poll::Ready(self.inner.try_send(data.take().unwrap(), None));
+        }
+
+        match self.inner.poll_reserve(cx) {
+            Poll::Ready(true) if self.inner.is_rendezvous() => {
+                let (notifier, receipt) = receipt::new();
+                self.inner.push(data.take().unwrap(), Some(notifier));
+                self.handoff = Some(receipt);
+
+                self.poll_handoff(cx)
+            }
+            Poll::Ready(true) => {
+                self.inner.push(data.take().unwrap(), None);
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(false) => Poll::Ready(Err(SendError::Closed(data.take().unwrap()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// Tries to clone the sender, returning
+    /// `Err(CloneError::Limit)` if the channel already has
+    /// the maximum number of senders that it can have.
+    pub(crate) fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        if !self.disconnected && !self.inner.try_add_sender() {
+            return Err(CloneError::Limit);
+        }
+
+        Ok(Sender {
+            disconnected: self.disconnected,
+            pending: None,
+            handoff: None,
+            inner: self.inner.clone(),
+        })
+    }
+
+    /// Tries to disconnect the sender from the channel
+    /// (see [`bounded::Sender::disconnect`]).
+    ///
+    /// [`bounded::Sender::disconnect`]: ../bounded/struct.Sender.html#method.disconnect
+    pub(crate) fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.inner.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.inner.remove_sender();
+            self.disconnected = true;
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel (see
+    /// [`bounded::Sender::close`]).
+    ///
+    /// [`bounded::Sender::close`]: ../bounded/struct.Sender.html#method.close
+    pub(crate) fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks whether the receiver has taken the message
+    /// that the sender handed off to it, registering the
+    /// task to be woken up when it does if it hasn't.
+    fn poll_handoff(&mut self, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        let handoff = match self.handoff {
+            Some(ref mut handoff) => handoff,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        // NOTE: the receipt resolves to an error if the receiver
+        //   has been dropped before taking the message, which
+        //   can't be given back anymore.
+        match Pin::new(handoff).poll(cx) {
+            Poll::Ready(_) => {
+                self.handoff = None;
+                Poll::Ready(Ok(()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// Tries to send the message that the sender is
+    /// waiting to send (when used as a [`Sink`]), if
+    /// any, giving it back if it failed to.
+    ///
+    /// This implements both `Sink::poll_ready` and
+    /// `Sink::poll_flush`.
+    ///
+    /// [`Sink`]: https://docs.rs/futures-sink-preview/0.3.0-alpha.16/futures_sink/trait.Sink.html
+    pub(crate) fn poll_pending(&mut self, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        // NOTE: a message handed off to the receiver of a
+        //   rendezvous channel has only been sent once the
+        //   receiver has taken it.
+        if self.pending.is_none() {
+            return self.poll_handoff(cx);
+        }
+
+        let mut pending = self.pending.take();
+        let poll = self.poll_send(cx, &mut pending);
+        self.pending = pending;
+
+        poll
+    }
+
+    /// Implements `Sink::start_send`: if the channel's
+    /// buffer is full, the message is kept until
+    /// [`poll_pending`] or [`poll_close`] manage to send
+    /// it, or return it in an error if they can't.
+    ///
+    /// [`poll_pending`]: #method.poll_pending
+    /// [`poll_close`]: #method.poll_close
+    pub(crate) fn start_send(&mut self, msg: D) -> Result<(), SendError<D>> {
+        if self.pending.is_some() {
+            return Err(SendError::Full(msg));
+        }
+
+        // NOTE: over a rendezvous channel, the message is
+        //   always kept, so that `poll_flush` hands it off and
+        //   waits for the receiver to take it.
+        if self.inner.is_rendezvous() && !self.disconnected {
+            self.pending = Some(msg);
+            return Ok(());
+        }
+
+        match self.try_send(msg) {
+            Err(SendError::Full(msg)) => {
+                self.pending = Some(msg);
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Implements `Sink::poll_close`, sending the message
+    /// that the sender is waiting to send (if any) before
+    /// disconnecting it from the channel.
+    pub(crate) fn poll_close(&mut self, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        match self.poll_pending(cx) {
+            Poll::Ready(Ok(())) => (),
+            poll => return poll,
+        }
+
+        if !self.disconnected {
+            self.inner.remove_sender();
+            self.disconnected = true;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<D> Receiver<D> {
+    /// Creates the state of the only receiver of a
+    /// single-consumer channel.
+    pub(crate) fn new(inner: Arc<Inner<D>>) -> Receiver<D> {
+        Receiver {
+            disconnected: false,
+            waiting: false,
+            shared: false,
+            inner,
+        }
+    }
+
+    /// Creates the state of a receiver of a
+    /// multi-consumer channel.
+    pub(crate) fn shared(inner: Arc<Inner<D>>) -> Receiver<D> {
+        Receiver {
+            disconnected: false,
+            waiting: false,
+            shared: true,
+            inner,
+        }
+    }
+
+    /// The state shared by the senders and the receivers
+    /// of the channel.
+    pub(crate) fn inner(&self) -> &Inner<D> {
+        &self.inner
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Whether the receiver won't receive any message
+    /// anymore.
+    pub(crate) fn is_terminated(&self) -> bool {
+        self.disconnected || self.inner.is_terminated()
+    }
+
+    /// Tries to receive a message over the channel (see
+    /// [`bounded::Receiver::try_recv`]).
+    ///
+    /// [`bounded::Receiver::try_recv`]: ../bounded/struct.Receiver.html#method.try_recv
+    pub(crate) fn try_recv(&mut self) -> Result<D, ReceiveError> {
+        if self.disconnected {
+            return Err(ReceiveError::Disconnected);
+        }
+
+        let consumer = self.lock_consumer();
+        // SAFETY: the receiver holds the consumer lock if the
+        //   channel can have more than one receiver or if the
+        //   senders can evict messages.
+        let res = unsafe { self.inner.try_recv() };
+        drop(consumer);
+        if res.is_ok() {
+            // NOTE: a message can only be handed off to the
+            //   receiver while it is waiting, and it stops
+            //   waiting when it is.
+            self.waiting = false;
+        }
+
+        res
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available (and, if the channel
+    /// is a rendezvous channel, letting the senders know
+    /// that the receiver is waiting for one).
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        if self.disconnected {
+            return Poll::Ready(Err(ReceiveError::Disconnected));
+        }
+
+        let consumer = self.lock_consumer();
+        // SAFETY: see `try_recv`.
+        let poll = unsafe { self.inner.poll_recv(cx) };
+        drop(consumer);
+
+        match poll {
+            Poll::Ready(Ok(data)) => {
+                self.waiting = false;
+                Poll::Ready(Ok(data))
+            }
+            Poll::Ready(Err(err)) => {
+                self.stop_waiting();
+                Poll::Ready(Err(err))
+            }
+            Poll::Pending if self.inner.is_rendezvous() && !self.waiting => {
+                self.waiting = true;
+                self.inner.start_waiting();
+                Poll::Pending
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// Lets the senders know that the receiver isn't
+    /// waiting for a message anymore (if the channel is a
+    /// rendezvous channel), because the future or select
+    /// that was polling it has been dropped or resolved.
+    ///
+    /// If a message has already been handed off to the
+    /// receiver, it will still receive it.
+    pub(crate) fn stop_waiting(&mut self) {
+        if self.waiting && self.inner.stop_waiting() {
+            self.waiting = false;
+        }
+    }
+
+    /// Tries to clone the receiver, returning
+    /// `Err(CloneError::Limit)` if the channel already has
+    /// the maximum number of receivers that it can have.
+    pub(crate) fn try_clone(&self) -> Result<Receiver<D>, CloneError> {
+        if !self.disconnected && !self.inner.try_add_receiver() {
+            return Err(CloneError::Limit);
+        }
+
+        Ok(Receiver {
+            disconnected: self.disconnected,
+            waiting: false,
+            shared: self.shared,
+            inner: self.inner.clone(),
+        })
+    }
+
+    /// Tries to disconnect the receiver from the channel,
+    /// closing it and dropping the messages still in its
+    /// buffer if it was the last receiver (see
+    /// [`bounded::Receiver::disconnect`]).
+    ///
+    /// [`bounded::Receiver::disconnect`]: ../bounded/struct.Receiver.html#method.disconnect
+    pub(crate) fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.inner.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.remove();
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel (see
+    /// [`bounded::Receiver::close`]).
+    ///
+    /// [`bounded::Receiver::close`]: ../bounded/struct.Receiver.html#method.close
+    pub(crate) fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Disconnects the receiver from the channel, draining
+    /// its buffer if it was the last receiver.
+    fn remove(&mut self) {
+        self.stop_waiting();
+        self.disconnected = true;
+        if self.inner.remove_receiver() {
+            let _consumer = self.lock_consumer();
+            // SAFETY: see `try_recv`.
+            unsafe { self.inner.drain() };
+        }
+    }
+
+    /// Acquires the consumer lock if the channel can have
+    /// more than one receiver, or if the senders can evict
+    /// messages from the channel's buffer (see
+    /// [`Overflow::DropOldest`]).
+    ///
+    /// [`Overflow::DropOldest`]: ../bounded/enum.Overflow.html#variant.DropOldest
+    fn lock_consumer(&self) -> Option<MutexGuard<'_, ()>> {
+        if self.shared || self.inner.shares_consumer() {
+            Some(self.inner.lock_consumer())
+        } else {
+            None
+        }
+    }
+}
+
+impl<D> Drop for Sender<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_sender();
+        }
+    }
+}
+
+impl<D> Drop for Receiver<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.remove();
+        }
+    }
+}
+
+// SAFETY: `Message` is `#[repr(C)]` and its first field
+//   is its link.
+unsafe impl<D> Linked for Message<D> {}
+
+impl<D> Message<D> {
+    pub(crate) fn new(data: D, notifier: Option<Notifier>) -> Message<D> {
+        Message {
+            link: Link::new(),
+            data,
+            notifier,
+        }
+    }
+
+    /// Notifies the message's read receipt (if any) that
+    /// it has been received, returning its data.
+    pub(crate) fn received(self) -> D {
+        if let Some(notifier) = self.notifier {
+            notifier.notify();
+        }
+
+        self.data
+    }
+}
+
+/// Increments `count` unless it has already reached
+/// `max`, returning whether it did.
+fn try_increment(count: &AtomicUsize, max: Option<usize>) -> bool {
+    let max = match max {
+        Some(max) => max,
+        None => {
+            count.fetch_add(1, Ordering::SeqCst);
+            return true;
+        }
+    };
+
+    let mut current = count.load(Ordering::SeqCst);
+    loop {
+        if current >= max {
+            return false;
+        }
+
+        match count.compare_exchange_weak(current, current + 1, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => return true,
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+/// Decrements `count` unless it is `0`, returning
+/// whether it did.
+fn try_decrement(count: &AtomicUsize) -> bool {
+    let mut current = count.load(Ordering::SeqCst);
+    loop {
+        if current == 0 {
+            return false;
+        }
+
+        match count.compare_exchange_weak(current, current - 1, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => return true,
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+impl<D> fmt::Debug for Inner<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Inner")
+            .field("len", &self.len.load(Ordering::SeqCst))
+            .field("buf", &self.buf)
+            .field("waiting", &self.waiting.load(Ordering::SeqCst))
+            .field("remaining", &self.remaining())
+            .field("closed", &self.is_closed())
+            .field("senders", &self.senders.load(Ordering::SeqCst))
+            .field("max_senders", &self.max_senders)
+            .field("receivers", &self.receivers.load(Ordering::SeqCst))
+            .field("max_receivers", &self.max_receivers)
+            .field("overflow", &self.overflow)
+            .field("evicted", &self.evicted())
+            .finish()
+    }
+}
@@ -1,7 +1,26 @@
+pub mod ask;
 pub mod bounded;
+pub mod broadcast;
+pub mod builder;
 pub mod channel;
+pub mod duplex;
 pub mod error;
+pub mod future;
+pub mod mpmc;
 pub mod once;
+pub mod priority;
+pub mod promise;
+pub mod quota;
+pub mod receipt;
+pub mod select;
+pub mod timer;
 pub mod unbounded;
+pub mod watch;
 
+mod blocking;
+mod inner;
+mod queue;
+mod wakers;
+
+pub use builder::ChannelBuilder;
 pub use error::*;
@@ -0,0 +1,475 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::stream::FusedStream;
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+
+use crate::blocking;
+use crate::error::*;
+use crate::future;
+use crate::future::PollSend;
+use crate::inner;
+use crate::inner::Inner;
+use crate::receipt::Receipt;
+use crate::timer::Timer;
+
+/// Creates a new bounded multi-consumer channel, which
+/// can hold exactly `buf` messages, no matter how many
+/// senders it has.
+///
+/// # Panics
+///
+/// This function panics if `buf` is `0`, since
+/// multi-consumer channels can't be rendezvous channels.
+pub fn bounded<D>(buf: usize) -> (Sender<D>, Receiver<D>) {
+    assert!(buf > 0, "a multi-consumer channel needs a buffer");
+
+    let inner = Arc::new(Inner::new(Some(buf)));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+/// Creates a new unbounded multi-consumer channel.
+pub fn unbounded<D>() -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(None));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+/// Creates a new bounded multi-consumer channel (see
+/// [`bounded`]), which can have at most `max_senders`
+/// senders and `max_receivers` receivers at the same
+/// time (or an unlimited number of them if they are
+/// `None`).
+///
+/// If `max_senders` is `Some(1)`, the channel uses a
+/// faster single-producer queue.
+///
+/// # Panics
+///
+/// This function panics if `buf` is `0`, or if
+/// `max_senders` or `max_receivers` is `Some(0)`.
+///
+/// [`bounded`]: fn.bounded.html
+pub fn bounded_with_limits<D>(
+    buf: usize,
+    max_senders: Option<usize>,
+    max_receivers: Option<usize>,
+) -> (Sender<D>, Receiver<D>) {
+    assert!(buf > 0, "a multi-consumer channel needs a buffer");
+
+    let inner = Arc::new(Inner::new(Some(buf)).with_limits(max_senders, max_receivers));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+/// Creates a new unbounded multi-consumer channel (see
+/// [`unbounded`]), which can have at most `max_senders`
+/// senders and `max_receivers` receivers at the same
+/// time (see [`bounded_with_limits`]).
+///
+/// [`unbounded`]: fn.unbounded.html
+/// [`bounded_with_limits`]: fn.bounded_with_limits.html
+pub fn unbounded_with_limits<D>(
+    max_senders: Option<usize>,
+    max_receivers: Option<usize>,
+) -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(None).with_limits(max_senders, max_receivers));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+#[derive(Debug)]
+/// The sending half of a multi-consumer channel, which
+/// shares the channel's state with the other senders and
+/// the receivers.
+pub struct Sender<D> {
+    sender: inner::Sender<D>,
+}
+
+/// A future that sends data over a multi-consumer channel
+/// once there is a free slot in its buffer (see
+/// [`Sender::send`]).
+///
+/// [`Sender::send`]: struct.Sender.html#method.send
+pub type SendFuture<'s, D> = future::SendFuture<'s, Sender<D>, D>;
+
+#[derive(Debug)]
+/// A receiving half of a multi-consumer channel, which
+/// shares the channel's state with the senders and the
+/// other receivers.
+///
+/// Every message sent over the channel is received by
+/// exactly one of the receivers.
+pub struct Receiver<D> {
+    receiver: inner::Receiver<D>,
+}
+
+/// A future that receives a message over a multi-consumer
+/// channel (see [`Receiver::recv`]).
+///
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+pub type RecvFuture<'r, D> = future::RecvFuture<'r, Receiver<D>>;
+
+/// A future that sends data over a multi-consumer
+/// channel once there is a free slot in its buffer,
+/// unless its deadline is reached first (see
+/// [`Sender::send_within`]).
+///
+/// [`Sender::send_within`]: struct.Sender.html#method.send_within
+pub type SendTimeoutFuture<'s, D, T> = future::SendTimeoutFuture<'s, Sender<D>, D, T>;
+
+/// A future that receives a message over a multi-consumer
+/// channel, unless its deadline is reached first (see
+/// [`Receiver::recv_within`]).
+///
+/// [`Receiver::recv_within`]: struct.Receiver.html#method.recv_within
+pub type RecvTimeoutFuture<'r, D, T> = future::RecvTimeoutFuture<'r, Receiver<D>, T>;
+
+impl<D> Sender<D> {
+    pub(crate) fn new(inner: Arc<Inner<D>>) -> Sender<D> {
+        Sender {
+            sender: inner::Sender::new(inner),
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the senders or receivers).
+    pub fn is_closed(&self) -> bool {
+        self.sender.inner().is_closed()
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.sender.is_disconnected()
+    }
+
+    /// Tries to send `data` over the channel, returning
+    /// `Ok(())` if it has been successfully sent, or either
+    /// `Err(SendError::Full)` if the channel's buffer is full,
+    /// `Err(SendError::Disconnected)` if the  sender has
+    /// disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been closed.
+    pub fn try_send(&mut self, data: D) -> Result<(), SendError<D>> {
+        self.sender.try_send(data)
+    }
+
+    /// Tries to send `data` over the channel (see
+    /// [`try_send`]), returning a [`Receipt`] that resolves
+    /// once it has been received if it has been successfully
+    /// sent.
+    ///
+    /// [`try_send`]: #method.try_send
+    /// [`Receipt`]: ../receipt/struct.Receipt.html
+    pub fn try_send_notify(&mut self, data: D) -> Result<Receipt, SendError<D>> {
+        self.sender.try_send_notify(data)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// once there is a free slot in its buffer, resolving
+    /// to `Ok(())` if it has been successfully sent, or
+    /// either `Err(SendError::Disconnected)` if the sender
+    /// has disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been
+    /// closed in the meantime.
+    pub fn send(&mut self, data: D) -> SendFuture<'_, D> {
+        SendFuture::new(self, data)
+    }
+
+    /// Sends `data` over the channel (see [`send`]),
+    /// blocking the current thread until there is a free
+    /// slot in the channel's buffer.
+    ///
+    /// [`send`]: #method.send
+    pub fn send_blocking(&mut self, data: D) -> Result<(), SendError<D>> {
+        blocking::send(self, data)
+    }
+
+    /// Sends `data` over the channel (see
+    /// [`send_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(SendError::Timeout)` if it couldn't be sent in
+    /// time.
+    ///
+    /// [`send_blocking`]: #method.send_blocking
+    pub fn send_timeout(&mut self, data: D, timeout: Duration) -> Result<(), SendError<D>> {
+        blocking::send_until(self, data, Instant::now() + timeout)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// (see [`send`]), or resolves to
+    /// `Err(SendError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_within<T: Timer>(
+        &mut self,
+        data: D,
+        timeout: Duration,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.send_deadline(data, deadline, timer)
+    }
+
+    /// Returns a future that sends `data` over the channel
+    /// (see [`send`]), or resolves to
+    /// `Err(SendError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_deadline<T: Timer>(
+        &mut self,
+        data: D,
+        deadline: Instant,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        SendTimeoutFuture::new(self, data, deadline, timer)
+    }
+
+    /// Tries to clone the sender, returning the new sender if
+    /// it succeeded, or `Err(CloneError::Limit)` if the
+    /// channel already has the maximum number of senders
+    /// that it can have.
+    pub fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        Ok(Sender {
+            sender: self.sender.try_clone()?,
+        })
+    }
+
+    /// Tries to disconnect the sender from the channel,
+    /// returning `Ok(())` if it succeeded, or either
+    /// `Err(DiconnectError::Disconnected)` if the sender
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        self.sender.disconnect()
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the sender already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        self.sender.close()
+    }
+}
+
+impl<D> Receiver<D> {
+    pub(crate) fn new(inner: Arc<Inner<D>>) -> Receiver<D> {
+        Receiver {
+            receiver: inner::Receiver::shared(inner),
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the receivers or senders).
+    pub fn is_closed(&self) -> bool {
+        self.receiver.inner().is_closed()
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.receiver.is_disconnected()
+    }
+
+    /// Tries to receive a message over the channel, returning
+    /// `Ok(D)` if it has received one, and either
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// closed.
+    pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel, resolving to `Ok(D)` once it has received
+    /// one, or either to `Err(ReceiveError::Disconnected)`
+    /// if the receiver has disconnected itself from the
+    /// channel or to `Err(ReceiveError::Closed)` if the
+    /// channel has been closed and its buffer is empty.
+    pub fn recv(&mut self) -> RecvFuture<'_, D> {
+        RecvFuture::new(self)
+    }
+
+    /// Receives a message over the channel (see [`recv`]),
+    /// blocking the current thread until there is one
+    /// available.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_blocking(&mut self) -> Result<D, ReceiveError> {
+        blocking::recv(self)
+    }
+
+    /// Receives a message over the channel (see
+    /// [`recv_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if no message has been
+    /// received in time.
+    ///
+    /// [`recv_blocking`]: #method.recv_blocking
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::recv_until(self, Instant::now() + timeout)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.recv_deadline(deadline, timer)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        RecvTimeoutFuture::new(self, deadline, timer)
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.receiver.poll_recv(cx)
+    }
+
+    /// Tries to clone the receiver, returning the new receiver if
+    /// it succeeded, or `Err(CloneError::Limit)` if the
+    /// channel already has the maximum number of receivers
+    /// that it can have.
+    pub fn try_clone(&self) -> Result<Receiver<D>, CloneError> {
+        Ok(Receiver {
+            receiver: self.receiver.try_clone()?,
+        })
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (closing it and dropping the messages still in its
+    /// buffer if it was the last receiver), returning
+    /// `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        self.receiver.disconnect()
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        self.receiver.close()
+    }
+}
+
+impl<D> Unpin for Sender<D> {}
+impl<D> Unpin for Receiver<D> {}
+
+impl<D> PollSend<D> for Sender<D> {
+    fn poll_send(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>> {
+        self.sender.poll_send(cx, data)
+    }
+}
+
+impl<D> Sink<D> for Sender<D> {
+    // NOTE: if the channel's buffer is full, `start_send`
+    //   keeps the message until `poll_ready`, `poll_flush` or
+    //   `poll_close` manage to send it, or return it in an
+    //   error if they can't.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        self.get_mut().sender.start_send(msg)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_close(cx)
+    }
+}
+
+impl<D> FusedStream for Receiver<D> {
+    fn is_terminated(&self) -> bool {
+        self.receiver.is_terminated()
+    }
+}
+
+impl<D> Stream for Receiver<D> {
+    type Item = D;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        match self.get_mut().poll_recv(cx) {
+            Poll::Ready(Ok(data)) => Poll::Ready(Some(data)),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<D> Clone for Sender<D> {
+    /// # Panics
+    ///
+    /// This method panics if the channel already has the
+    /// maximum number of senders that it can have (see
+    /// [`try_clone`]).
+    ///
+    /// [`try_clone`]: #method.try_clone
+    fn clone(&self) -> Sender<D> {
+        self.try_clone()
+            .expect("the channel already has the maximum number of senders")
+    }
+}
+
+impl<D> Clone for Receiver<D> {
+    /// # Panics
+    ///
+    /// This method panics if the channel already has the
+    /// maximum number of receivers that it can have (see
+    /// [`try_clone`]).
+    ///
+    /// [`try_clone`]: #method.try_clone
+    fn clone(&self) -> Receiver<D> {
+        self.try_clone()
+            .expect("the channel already has the maximum number of receivers")
+    }
+}
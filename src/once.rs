@@ -1,12 +1,19 @@
 use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use futures_channel::oneshot;
 use futures_channel::oneshot::Receiver as FutReceiver;
 use futures_channel::oneshot::Sender as FutSender;
 use futures_core::future::Future;
 
+use crate::blocking;
 use crate::error::*;
 
 /// Creates a new single use channel (see [`futures-channel`'s
@@ -15,10 +22,22 @@ use crate::error::*;
 /// [`futures-channel`'s documentation]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/oneshot/index.html
 pub fn new<D>() -> (Sender<D>, Receiver<D>) {
     let (sender, receiver) = oneshot::channel();
+    let dropped = Arc::new(AtomicUsize::new(NOT_DROPPED));
 
-    (Sender::new(sender), Receiver::new(receiver))
+    (
+        Sender::new(sender, dropped.clone()),
+        Receiver::new(receiver, dropped),
+    )
 }
 
+/// The sender hasn't been dropped without sending data.
+const NOT_DROPPED: usize = 0;
+/// The sender has been dropped without sending data.
+const DROPPED: usize = 1;
+/// The sender has been dropped without sending data
+/// while its thread was panicking.
+const PANICKED: usize = 2;
+
 #[derive(Debug)]
 /// A wrapper around a [`oneshot::Sender`] that doesn't consume
 /// itself when sending data and stores it state after doing so.
@@ -29,7 +48,22 @@ pub struct Sender<D> {
     pub sent: bool,
     /// Whether the channel has been cancelled.
     pub cancelled: bool,
+    /// Whether the channel has been closed.
+    pub closed: bool,
     sender: Option<FutSender<D>>,
+    /// Whether the sender has been dropped without sending
+    /// data (shared with the receiver).
+    dropped: Arc<AtomicUsize>,
+}
+
+#[derive(Debug)]
+/// A future that resolves once the receiving half of a
+/// single use channel has been closed or dropped (see
+/// [`Sender::cancelled`]).
+///
+/// [`Sender::cancelled`]: struct.Sender.html#method.cancelled
+pub struct CancelledFuture<'s, D> {
+    sender: &'s mut Sender<D>,
 }
 
 #[derive(Debug)]
@@ -46,14 +80,19 @@ pub struct Receiver<D> {
     /// Whether the channel has been cancelled.
     pub cancelled: bool,
     receiver: Option<FutReceiver<D>>,
+    /// Whether the sender has been dropped without sending
+    /// data (shared with the sender).
+    dropped: Arc<AtomicUsize>,
 }
 
 impl<D> Sender<D> {
-    pub(crate) fn new(sender: FutSender<D>) -> Sender<D> {
+    fn new(sender: FutSender<D>, dropped: Arc<AtomicUsize>) -> Sender<D> {
         Sender {
             sent: false,
             cancelled: false,
+            closed: false,
             sender: Some(sender),
+            dropped,
         }
     }
 
@@ -67,36 +106,94 @@ impl<D> Sender<D> {
             match sender.send(data) {
                 Ok(()) => {
                     self.sent = true;
-                    return Ok(());
+                    Ok(())
                 }
                 Err(data) => {
                     self.cancelled = true;
-                    return Err(SendError::Closed(data));
+                    Err(SendError::Closed(data))
                 }
             }
         } else if self.sent {
             Err(SendError::Full(data))
-        } else if self.cancelled {
+        } else if self.cancelled || self.closed {
             Err(SendError::Closed(data))
         } else {
             unreachable!();
         }
     }
+
+    /// Whether the receiver has been closed or dropped
+    /// before a message could be sent over the channel.
+    pub fn is_cancelled(&self) -> bool {
+        if let Some(ref sender) = self.sender {
+            sender.is_canceled()
+        } else {
+            self.cancelled
+        }
+    }
+
+    /// Returns `Poll::Ready(())` if the receiver has been
+    /// closed or dropped (or if the sender can't send a
+    /// message anymore), or registers the task to be woken
+    /// up when it is.
+    pub fn poll_cancelled(&mut self, cx: &mut Context) -> Poll<()> {
+        if let Some(ref mut sender) = self.sender {
+            match sender.poll_cancel(cx) {
+                Poll::Ready(()) => {
+                    self.cancelled = true;
+                    self.sender = None;
+                    Poll::Ready(())
+                }
+                Poll::Pending => Poll::Pending,
+            }
+        } else {
+            Poll::Ready(())
+        }
+    }
+
+    /// Returns a future that resolves once the receiver
+    /// has been closed or dropped (see [`poll_cancelled`]),
+    /// allowing to stop computing a message that wouldn't
+    /// be received.
+    ///
+    /// [`poll_cancelled`]: #method.poll_cancelled
+    pub fn cancelled(&mut self) -> CancelledFuture<'_, D> {
+        CancelledFuture { sender: self }
+    }
+
+    /// Tries to close the channel without sending a message
+    /// over it, returning `Ok(())` if it succeeded or
+    /// `Err(CloseError::Closed)` if a message has already been
+    /// sent over the channel or if it has already been
+    /// cancelled or closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.sender.take().is_some() {
+            self.closed = true;
+            Ok(())
+        } else {
+            Err(CloseError::Closed)
+        }
+    }
 }
 
 impl<D> Receiver<D> {
-    pub(crate) fn new(receiver: FutReceiver<D>) -> Receiver<D> {
+    fn new(receiver: FutReceiver<D>, dropped: Arc<AtomicUsize>) -> Receiver<D> {
         Receiver {
             received: false,
             closed: false,
             cancelled: false,
             receiver: Some(receiver),
+            dropped,
         }
     }
 
     /// Tries to receive a message over the channel, returning
     /// `Ok(D)` if it has received one and either
-    /// `Err(ReceiveError::Empty)` if it hasn't or
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::SenderDropped)` if the sender has
+    /// been dropped without sending one,
+    /// `Err(ReceiveError::SenderPanicked)` if it has been
+    /// dropped while its thread was panicking or
     /// `Err(ReceiveError::Closed)` if the channel has been
     /// cancelled or closed.
     pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
@@ -104,20 +201,43 @@ impl<D> Receiver<D> {
             match receiver.try_recv() {
                 Ok(Some(data)) => {
                     self.received = true;
-                    return Ok(data);
+                    Ok(data)
                 }
-                Ok(None) => return Err(ReceiveError::Empty),
+                Ok(None) => Err(ReceiveError::Empty),
                 Err(_) => {
                     self.cancelled = true;
                     self.receiver = None;
-                    return Err(ReceiveError::Closed);
+                    Err(self.cancellation())
                 }
             }
+        } else if self.cancelled {
+            Err(self.cancellation())
         } else {
-            return Err(ReceiveError::Closed);
+            Err(ReceiveError::Closed)
         }
     }
 
+    /// Blocks the current thread until a message has been
+    /// received over the channel, returning `Ok(D)` if one
+    /// has been, or the error that [`try_recv`] would have
+    /// returned if the channel has been cancelled or closed.
+    ///
+    /// [`try_recv`]: #method.try_recv
+    pub fn wait(&mut self) -> Result<D, ReceiveError> {
+        blocking::wait(|cx| Pin::new(&mut *self).poll(cx))
+    }
+
+    /// Blocks the current thread until a message has been
+    /// received over the channel (see [`wait`]), for at
+    /// most `timeout`, returning `Err(ReceiveError::Timeout)`
+    /// if none has been in time.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::wait_until(Instant::now() + timeout, |cx| Pin::new(&mut *self).poll(cx))
+            .unwrap_or(Err(ReceiveError::Timeout))
+    }
+
     /// Tries to close the channel, returning `Ok(())` if
     /// it succeeded or `Err(CloseError::Closed)` it the
     /// chanenl has already been closed.
@@ -131,10 +251,29 @@ impl<D> Receiver<D> {
             Err(CloseError::Closed)
         }
     }
+
+    /// Returns the error explaining why the channel has
+    /// been cancelled.
+    fn cancellation(&self) -> ReceiveError {
+        match self.dropped.load(Ordering::SeqCst) {
+            DROPPED => ReceiveError::SenderDropped,
+            PANICKED => ReceiveError::SenderPanicked,
+            _ => ReceiveError::Closed,
+        }
+    }
 }
 
 impl<D> Unpin for Sender<D> {}
 impl<D> Unpin for Receiver<D> {}
+impl<D> Unpin for CancelledFuture<'_, D> {}
+
+impl<D> Future for CancelledFuture<'_, D> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        self.get_mut().sender.poll_cancelled(cx)
+    }
+}
 
 impl<D> Future for Receiver<D> {
     type Output = Result<D, ReceiveError>;
@@ -149,12 +288,31 @@ impl<D> Future for Receiver<D> {
                 }
                 Poll::Ready(Err(_)) => {
                     receiver.cancelled = true;
-                    Poll::Ready(Err(ReceiveError::Closed))
+                    receiver.receiver = None;
+                    Poll::Ready(Err(receiver.cancellation()))
                 }
                 Poll::Pending => Poll::Pending,
             }
+        } else if receiver.cancelled {
+            Poll::Ready(Err(receiver.cancellation()))
         } else {
             Poll::Ready(Err(ReceiveError::Closed))
         }
     }
 }
+
+impl<D> Drop for Sender<D> {
+    fn drop(&mut self) {
+        // NOTE: the receiver is only notified once the inner
+        //   sender is dropped, after this has been stored.
+        if self.sender.is_some() {
+            let dropped = if thread::panicking() {
+                PANICKED
+            } else {
+                DROPPED
+            };
+
+            self.dropped.store(dropped, Ordering::SeqCst);
+        }
+    }
+}
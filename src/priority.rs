@@ -0,0 +1,911 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::future::Future;
+use futures_core::stream::FusedStream;
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+
+use crate::blocking;
+use crate::error::*;
+use crate::future;
+use crate::future::PollSend;
+use crate::inner::Message;
+use crate::receipt;
+use crate::receipt::Notifier;
+use crate::receipt::Receipt;
+use crate::timer::Timer;
+use crate::wakers::Wakers;
+
+/// Creates a new priority channel, which has one lane
+/// per item of `caps`, each of them able to hold as many
+/// messages as the item's value.
+///
+/// The first lane has the highest priority and the last
+/// one the lowest: the receiver always receives the
+/// messages of a lane before the ones of the lanes that
+/// follow it.
+///
+/// # Panics
+///
+/// This function panics if `caps` is empty or if any of
+/// its items is `0`.
+pub fn new<D>(caps: &[usize]) -> (Sender<D>, Receiver<D>) {
+    assert!(!caps.is_empty(), "a priority channel needs a lane");
+    assert!(
+        caps.iter().all(|cap| *cap > 0),
+        "a priority channel's lanes need a buffer"
+    );
+
+    let inner = Arc::new(Inner::new(caps, None));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+/// Creates a new priority channel (see [`new`]) whose
+/// lower-priority lanes can't starve: once a lane that
+/// has messages has been skipped `aging` times in a row
+/// in favor of other lanes, its oldest message is
+/// received next.
+///
+/// # Panics
+///
+/// This function panics if `caps` is empty, if any of its
+/// items is `0` or if `aging` is `0`.
+///
+/// [`new`]: fn.new.html
+pub fn with_aging<D>(caps: &[usize], aging: usize) -> (Sender<D>, Receiver<D>) {
+    assert!(!caps.is_empty(), "a priority channel needs a lane");
+    assert!(
+        caps.iter().all(|cap| *cap > 0),
+        "a priority channel's lanes need a buffer"
+    );
+    assert!(aging > 0, "a lane can't age without being skipped");
+
+    let inner = Arc::new(Inner::new(caps, Some(aging)));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+#[derive(Debug)]
+/// The sending half of a priority channel, which shares
+/// the channel's state with the other senders and the
+/// receiver.
+///
+/// A sender is bound to a lane, over which it sends its
+/// messages unless another one is specified (see
+/// [`try_send_to`] and [`send_to`]).
+///
+/// [`try_send_to`]: #method.try_send_to
+/// [`send_to`]: #method.send_to
+pub struct Sender<D> {
+    /// The lane that the sender is bound to (the one
+    /// with the lowest priority by default).
+    lane: usize,
+    /// Whether the sender has disconnected itself from the
+    /// channel.
+    disconnected: bool,
+    /// The message that the sender is waiting to send
+    /// (when used as a [`Sink`]).
+    ///
+    /// [`Sink`]: https://docs.rs/futures-sink-preview/0.3.0-alpha.16/futures_sink/trait.Sink.html
+    pending: Option<D>,
+    inner: Arc<Inner<D>>,
+}
+
+#[derive(Debug)]
+/// A future that sends data over a lane of a priority
+/// channel once there is a free slot in the lane's
+/// buffer (see [`Sender::send`]).
+///
+/// [`Sender::send`]: struct.Sender.html#method.send
+pub struct SendFuture<'s, D> {
+    sender: &'s mut Sender<D>,
+    lane: usize,
+    data: Option<D>,
+}
+
+#[derive(Debug)]
+/// The receiving half of a priority channel, which
+/// shares the channel's state with the senders.
+pub struct Receiver<D> {
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    disconnected: bool,
+    inner: Arc<Inner<D>>,
+}
+
+/// A future that receives a message over a priority
+/// channel (see [`Receiver::recv`]).
+///
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+pub type RecvFuture<'r, D> = future::RecvFuture<'r, Receiver<D>>;
+
+/// A future that sends data over the lane that a
+/// sender is bound to once there is a free slot in the
+/// lane's buffer, unless its deadline is reached first
+/// (see [`Sender::send_within`]).
+///
+/// [`Sender::send_within`]: struct.Sender.html#method.send_within
+pub type SendTimeoutFuture<'s, D, T> = future::SendTimeoutFuture<'s, Sender<D>, D, T>;
+
+/// A future that receives a message over a priority
+/// channel, unless its deadline is reached first (see
+/// [`Receiver::recv_within`]).
+///
+/// [`Receiver::recv_within`]: struct.Receiver.html#method.recv_within
+pub type RecvTimeoutFuture<'r, D, T> = future::RecvTimeoutFuture<'r, Receiver<D>, T>;
+
+/// The state shared by the senders and the receiver of
+/// a priority channel.
+struct Inner<D> {
+    state: Mutex<State<D>>,
+    /// The number of lanes of the channel.
+    lanes: usize,
+    /// The number of times in a row that a lane can be
+    /// skipped before its oldest message is received.
+    aging: Option<usize>,
+    /// The wakers of the receiver's task if it is waiting
+    /// for a message.
+    recv_wakers: Wakers,
+    /// The wakers of the senders' tasks that are waiting
+    /// for a slot of a lane's buffer to be freed.
+    send_wakers: Wakers,
+}
+
+struct State<D> {
+    /// The lanes of the channel, in decreasing order of
+    /// priority.
+    lanes: Vec<Lane<D>>,
+    /// Whether the channel has been closed.
+    closed: bool,
+    /// The number of connected senders.
+    senders: usize,
+}
+
+struct Lane<D> {
+    msgs: VecDeque<Message<D>>,
+    /// The number of messages that the lane can hold.
+    cap: usize,
+    /// The number of times in a row that the lane has been
+    /// skipped while it had messages.
+    skipped: usize,
+}
+
+impl<D> Sender<D> {
+    fn new(inner: Arc<Inner<D>>) -> Sender<D> {
+        Sender {
+            lane: inner.lanes - 1,
+            disconnected: false,
+            pending: None,
+            inner,
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the senders or by the receiver).
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().closed
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// The number of lanes of the channel.
+    pub fn lanes(&self) -> usize {
+        self.inner.lanes
+    }
+
+    /// The lane that the sender is bound to (the one with
+    /// the lowest priority by default, see [`bind`]).
+    ///
+    /// [`bind`]: #method.bind
+    pub fn lane(&self) -> usize {
+        self.lane
+    }
+
+    /// Returns a new sender, bound to `lane`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the channel doesn't have
+    /// this lane.
+    pub fn bind(&self, lane: usize) -> Sender<D> {
+        assert!(
+            lane < self.inner.lanes,
+            "the channel doesn't have this lane"
+        );
+
+        let mut sender = self.clone();
+        sender.lane = lane;
+        sender
+    }
+
+    /// Tries to send `data` over the lane that the sender
+    /// is bound to, returning `Ok(())` if it has been
+    /// successfully sent, or either `Err(SendError::Full)`
+    /// if the lane's buffer is full,
+    /// `Err(SendError::Disconnected)` if the  sender has
+    /// disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been closed.
+    pub fn try_send(&mut self, data: D) -> Result<(), SendError<D>> {
+        self.try_push(self.lane, data)
+    }
+
+    /// Tries to send `data` over `lane` (see [`try_send`]).
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the channel doesn't have
+    /// this lane.
+    ///
+    /// [`try_send`]: #method.try_send
+    pub fn try_send_to(&mut self, lane: usize, data: D) -> Result<(), SendError<D>> {
+        assert!(
+            lane < self.inner.lanes,
+            "the channel doesn't have this lane"
+        );
+
+        self.try_push(lane, data)
+    }
+
+    /// Tries to send `data` over `lane` (see [`try_send`]),
+    /// which has already been checked to be one of the
+    /// channel's lanes.
+    ///
+    /// [`try_send`]: #method.try_send
+    fn try_push(&mut self, lane: usize, data: D) -> Result<(), SendError<D>> {
+        if self.disconnected {
+            Err(SendError::Disconnected(data))
+        } else {
+            self.inner.try_send(lane, data, None)
+        }
+    }
+
+    /// Tries to send `data` over the lane that the sender
+    /// is bound to (see [`try_send`]), returning a
+    /// [`Receipt`] that resolves once it has been received
+    /// if it has been successfully sent.
+    ///
+    /// [`try_send`]: #method.try_send
+    /// [`Receipt`]: ../receipt/struct.Receipt.html
+    pub fn try_send_notify(&mut self, data: D) -> Result<Receipt, SendError<D>> {
+        if self.disconnected {
+            return Err(SendError::Disconnected(data));
+        }
+
+        let (notifier, receipt) = receipt::new();
+        self.inner.try_send(self.lane, data, Some(notifier))?;
+
+        Ok(receipt)
+    }
+
+    /// Returns a future that sends `data` over the lane
+    /// that the sender is bound to once there is a free
+    /// slot in the lane's buffer, resolving to `Ok(())` if
+    /// it has been successfully sent, or either
+    /// `Err(SendError::Disconnected)` if the sender has
+    /// disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been
+    /// closed in the meantime.
+    pub fn send(&mut self, data: D) -> SendFuture<'_, D> {
+        let lane = self.lane;
+        self.send_to(lane, data)
+    }
+
+    /// Returns a future that sends `data` over `lane` (see
+    /// [`send`]).
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the channel doesn't have
+    /// this lane.
+    ///
+    /// [`send`]: #method.send
+    pub fn send_to(&mut self, lane: usize, data: D) -> SendFuture<'_, D> {
+        assert!(
+            lane < self.inner.lanes,
+            "the channel doesn't have this lane"
+        );
+
+        SendFuture {
+            sender: self,
+            lane,
+            data: Some(data),
+        }
+    }
+
+    /// Sends `data` over the lane that the sender is bound
+    /// to (see [`send`]), blocking the current thread until
+    /// there is a free slot in the lane's buffer.
+    ///
+    /// [`send`]: #method.send
+    pub fn send_blocking(&mut self, data: D) -> Result<(), SendError<D>> {
+        blocking::send(self, data)
+    }
+
+    /// Sends `data` over the lane that the sender is bound to (see
+    /// [`send_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(SendError::Timeout)` if it couldn't be sent in
+    /// time.
+    ///
+    /// [`send_blocking`]: #method.send_blocking
+    pub fn send_timeout(&mut self, data: D, timeout: Duration) -> Result<(), SendError<D>> {
+        blocking::send_until(self, data, Instant::now() + timeout)
+    }
+
+    /// Returns a future that sends `data` over the lane
+    /// that the sender is bound to (see [`send`]), or
+    /// resolves to `Err(SendError::Timeout)` if it couldn't
+    /// before `timeout` has elapsed (as measured by
+    /// `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_within<T: Timer>(
+        &mut self,
+        data: D,
+        timeout: Duration,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.send_deadline(data, deadline, timer)
+    }
+
+    /// Returns a future that sends `data` over the lane
+    /// that the sender is bound to (see [`send`]), or
+    /// resolves to `Err(SendError::Timeout)` if it couldn't
+    /// before `deadline` (as measured by `timer`).
+    ///
+    /// [`send`]: #method.send
+    pub fn send_deadline<T: Timer>(
+        &mut self,
+        data: D,
+        deadline: Instant,
+        timer: T,
+    ) -> SendTimeoutFuture<'_, D, T> {
+        SendTimeoutFuture::new(self, data, deadline, timer)
+    }
+
+    /// Tries to send the data stored in `data` over
+    /// `lane`, taking it if the sender doesn't have to
+    /// wait for a slot to be freed.
+    fn poll_send_to(
+        &mut self,
+        cx: &mut Context,
+        lane: usize,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>> {
+        match self.try_push(lane, data.take().unwrap()) {
+            Err(SendError::Full(msg)) => *data = Some(msg),
+            res => return Poll::Ready(res),
+        }
+
+        self.inner.send_wakers.register(cx.waker());
+
+        match self.try_push(lane, data.take().unwrap()) {
+            Err(SendError::Full(msg)) => {
+                *data = Some(msg);
+                Poll::Pending
+            }
+            res => Poll::Ready(res),
+        }
+    }
+
+    /// Tries to clone the sender (see [`Clone`]), always
+    /// returning the new sender, since a priority channel
+    /// can have any number of senders.
+    ///
+    /// [`Clone`]: #impl-Clone
+    pub fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        if !self.disconnected {
+            self.inner.lock().senders += 1;
+        }
+
+        Ok(Sender {
+            lane: self.lane,
+            disconnected: self.disconnected,
+            pending: None,
+            inner: self.inner.clone(),
+        })
+    }
+
+    /// Tries to disconnect the sender from the channel,
+    /// returning `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the sender
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.inner.remove_sender();
+            self.disconnected = true;
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the sender already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Tries to send the message that the sender is
+    /// waiting to send (when used as a [`Sink`]), if
+    /// any, giving it back if it failed to.
+    ///
+    /// [`Sink`]: https://docs.rs/futures-sink-preview/0.3.0-alpha.16/futures_sink/trait.Sink.html
+    fn poll_pending(&mut self, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        if self.pending.is_none() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut pending = self.pending.take();
+        let poll = self.poll_send(cx, &mut pending);
+        self.pending = pending;
+
+        poll
+    }
+}
+
+impl<D> Receiver<D> {
+    fn new(inner: Arc<Inner<D>>) -> Receiver<D> {
+        Receiver {
+            disconnected: false,
+            inner,
+        }
+    }
+
+    /// Whether the channel has been closed (by the
+    /// receiver or by any of the senders).
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().closed
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// The number of lanes of the channel.
+    pub fn lanes(&self) -> usize {
+        self.inner.lanes
+    }
+
+    /// Tries to receive a message over the channel (the
+    /// oldest message of the lane with the highest priority
+    /// that has any, unless another lane has aged),
+    /// returning `Ok(D)` if it has received one, and either
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// closed.
+    pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
+        if self.disconnected {
+            return Err(ReceiveError::Disconnected);
+        }
+
+        let mut state = self.inner.lock();
+        if let Some(msg) = state.pop(self.inner.aging) {
+            drop(state);
+
+            self.inner.send_wakers.wake_all();
+            Ok(msg.received())
+        } else if state.closed {
+            Err(ReceiveError::Closed)
+        } else {
+            Err(ReceiveError::Empty)
+        }
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`try_recv`]), resolving to `Ok(D)` once
+    /// it has received one, or either to
+    /// `Err(ReceiveError::Disconnected)` if the receiver has
+    /// disconnected itself from the channel or to
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// closed and its lanes are empty.
+    ///
+    /// [`try_recv`]: #method.try_recv
+    pub fn recv(&mut self) -> RecvFuture<'_, D> {
+        RecvFuture::new(self)
+    }
+
+    /// Receives a message over the channel (see [`recv`]),
+    /// blocking the current thread until there is one
+    /// available.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_blocking(&mut self) -> Result<D, ReceiveError> {
+        blocking::recv(self)
+    }
+
+    /// Receives a message over the channel (see
+    /// [`recv_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if no message has been
+    /// received in time.
+    ///
+    /// [`recv_blocking`]: #method.recv_blocking
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::recv_until(self, Instant::now() + timeout)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.recv_deadline(deadline, timer)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        RecvTimeoutFuture::new(self, deadline, timer)
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        match self.try_recv() {
+            Err(ReceiveError::Empty) => (),
+            res => return Poll::Ready(res),
+        }
+
+        self.inner.recv_wakers.register(cx.waker());
+
+        match self.try_recv() {
+            Err(ReceiveError::Empty) => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (closing it and dropping the messages still in its
+    /// lanes, since it is its only receiver), returning
+    /// `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.disconnected = true;
+            self.inner.remove_receiver();
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<D> Inner<D> {
+    fn new(caps: &[usize], aging: Option<usize>) -> Inner<D> {
+        let lanes = caps
+            .iter()
+            .map(|cap| Lane {
+                msgs: VecDeque::with_capacity(*cap),
+                cap: *cap,
+                skipped: 0,
+            })
+            .collect();
+
+        Inner {
+            state: Mutex::new(State {
+                lanes,
+                closed: false,
+                senders: 1,
+            }),
+            lanes: caps.len(),
+            aging,
+            recv_wakers: Wakers::new(),
+            send_wakers: Wakers::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<D>> {
+        self.state.lock().unwrap()
+    }
+
+    /// Tries to push `data` (along with the notifier of its
+    /// read receipt) to `lane`.
+    fn try_send(
+        &self,
+        lane: usize,
+        data: D,
+        notifier: Option<Notifier>,
+    ) -> Result<(), SendError<D>> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(SendError::Closed(data));
+        }
+
+        // NOTE: `lane` has been checked by the sender.
+        let lane = &mut state.lanes[lane];
+        if lane.msgs.len() >= lane.cap {
+            return Err(SendError::Full(data));
+        }
+
+        lane.msgs.push_back(Message::new(data, notifier));
+        drop(state);
+
+        self.recv_wakers.wake_all();
+        Ok(())
+    }
+
+    /// Closes the channel, returning whether it wasn't
+    /// already closed.
+    fn close(&self) -> bool {
+        let mut state = self.lock();
+        if state.closed {
+            return false;
+        }
+
+        state.closed = true;
+        drop(state);
+
+        self.recv_wakers.wake_all();
+        self.send_wakers.wake_all();
+        true
+    }
+
+    /// Unregisters a sender, closing the channel if it
+    /// was the last one.
+    fn remove_sender(&self) {
+        let mut state = self.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.close();
+        }
+    }
+
+    /// Unregisters the receiver, closing the channel and
+    /// dropping the messages that are still in its lanes
+    /// (and thus notifying their read receipts that they
+    /// won't be received).
+    fn remove_receiver(&self) {
+        let mut state = self.lock();
+        let msgs = state
+            .lanes
+            .iter_mut()
+            .map(|lane| lane.msgs.drain(..).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        drop(state);
+
+        // NOTE: the messages are dropped after the lock has
+        //   been released, since dropping a notifier might
+        //   wake up a task.
+        drop(msgs);
+        self.close();
+    }
+}
+
+impl<D> State<D> {
+    /// Pops the message that should be received next: the
+    /// oldest message of the first lane that has any,
+    /// unless another lane has been skipped `aging` times
+    /// in a row (in which case the first of those lanes is
+    /// chosen instead).
+    fn pop(&mut self, aging: Option<usize>) -> Option<Message<D>> {
+        let first = self.lanes.iter().position(|lane| !lane.msgs.is_empty())?;
+
+        let aging = match aging {
+            Some(aging) => aging,
+            None => return self.lanes[first].msgs.pop_front(),
+        };
+
+        let chosen = self.lanes[first..]
+            .iter()
+            .position(|lane| !lane.msgs.is_empty() && lane.skipped >= aging)
+            .map_or(first, |aged| first + aged);
+
+        for (i, lane) in self.lanes.iter_mut().enumerate() {
+            if i == chosen {
+                lane.skipped = 0;
+            } else if !lane.msgs.is_empty() {
+                lane.skipped += 1;
+            }
+        }
+
+        self.lanes[chosen].msgs.pop_front()
+    }
+
+    /// Whether all the lanes are empty.
+    fn is_empty(&self) -> bool {
+        self.lanes.iter().all(|lane| lane.msgs.is_empty())
+    }
+}
+
+impl<D> Unpin for Sender<D> {}
+impl<D> Unpin for SendFuture<'_, D> {}
+impl<D> Unpin for Receiver<D> {}
+
+impl<D> Future for SendFuture<'_, D> {
+    type Output = Result<(), SendError<D>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), SendError<D>>> {
+        let fut = self.get_mut();
+        fut.sender.poll_send_to(cx, fut.lane, &mut fut.data)
+    }
+}
+
+impl<D> PollSend<D> for Sender<D> {
+    fn poll_send(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Option<D>,
+    ) -> Poll<Result<(), SendError<D>>> {
+        self.poll_send_to(cx, self.lane, data)
+    }
+}
+
+impl<D> Sink<D> for Sender<D> {
+    // NOTE: if the lane's buffer is full, `start_send`
+    //   keeps the message until `poll_ready`, `poll_flush` or
+    //   `poll_close` manage to send it, or return it in an
+    //   error if they can't.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        let sender = self.get_mut();
+        if sender.pending.is_some() {
+            return Err(SendError::Full(msg));
+        }
+
+        match sender.try_send(msg) {
+            Err(SendError::Full(msg)) => {
+                sender.pending = Some(msg);
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        let sender = self.get_mut();
+        match sender.poll_pending(cx) {
+            Poll::Ready(Ok(())) => (),
+            poll => return poll,
+        }
+
+        if !sender.disconnected {
+            sender.inner.remove_sender();
+            sender.disconnected = true;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<D> FusedStream for Receiver<D> {
+    fn is_terminated(&self) -> bool {
+        if self.disconnected {
+            return true;
+        }
+
+        let state = self.inner.lock();
+        state.closed && state.is_empty()
+    }
+}
+
+impl<D> Stream for Receiver<D> {
+    type Item = D;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        match self.get_mut().poll_recv(cx) {
+            Poll::Ready(Ok(data)) => Poll::Ready(Some(data)),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<D> Clone for Sender<D> {
+    fn clone(&self) -> Sender<D> {
+        self.try_clone()
+            .expect("a priority channel can have any number of senders")
+    }
+}
+
+impl<D> Drop for Sender<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_sender();
+        }
+    }
+}
+
+impl<D> Drop for Receiver<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_receiver();
+        }
+    }
+}
+
+impl<D> fmt::Debug for Inner<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.lock();
+        let lens = state
+            .lanes
+            .iter()
+            .map(|lane| lane.msgs.len())
+            .collect::<Vec<_>>();
+
+        f.debug_struct("Inner")
+            .field("lanes", &lens)
+            .field("aging", &self.aging)
+            .field("closed", &state.closed)
+            .field("senders", &state.senders)
+            .finish()
+    }
+}
@@ -0,0 +1,388 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::task::Context;
+use std::task::Poll;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::future::Future;
+
+use crate::blocking;
+use crate::error::*;
+use crate::wakers::Wakers;
+
+/// Creates a new promise, a single use channel (see
+/// [`once::new`]) whose receiver can be cloned, so that
+/// several tasks can wait for the same message.
+///
+/// [`once::new`]: ../once/fn.new.html
+pub fn new<D>() -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner {
+        state: Mutex::new(State {
+            data: None,
+            sender: SenderState::Pending,
+            receivers: 1,
+        }),
+        recv_wakers: Wakers::new(),
+        send_wakers: Wakers::new(),
+    });
+
+    (
+        Sender {
+            sent: false,
+            cancelled: false,
+            closed: false,
+            inner: inner.clone(),
+        },
+        Receiver {
+            received: false,
+            closed: false,
+            cancelled: false,
+            inner,
+        },
+    )
+}
+
+#[derive(Debug)]
+/// The sending half of a promise, which has the same API
+/// as a [`once::Sender`].
+///
+/// [`once::Sender`]: ../once/struct.Sender.html
+pub struct Sender<D> {
+    /// Whether data has already been sent over the channel.
+    pub sent: bool,
+    /// Whether the channel has been cancelled.
+    pub cancelled: bool,
+    /// Whether the channel has been closed.
+    pub closed: bool,
+    inner: Arc<Inner<D>>,
+}
+
+#[derive(Debug)]
+/// A future that resolves once all the receivers of a
+/// promise have been closed or dropped (see
+/// [`Sender::cancelled`]).
+///
+/// [`Sender::cancelled`]: struct.Sender.html#method.cancelled
+pub struct CancelledFuture<'s, D> {
+    sender: &'s mut Sender<D>,
+}
+
+#[derive(Debug)]
+/// A receiving half of a promise, which receives a clone
+/// of the message sent over it (even if it has been sent
+/// before the receiver was cloned).
+pub struct Receiver<D> {
+    /// Whether data has been received.
+    pub received: bool,
+    /// Whether the receiver has been closed.
+    pub closed: bool,
+    /// Whether the channel has been cancelled.
+    pub cancelled: bool,
+    inner: Arc<Inner<D>>,
+}
+
+/// The state shared by the sender and the receivers of a
+/// promise.
+struct Inner<D> {
+    state: Mutex<State<D>>,
+    /// The wakers of the receivers' tasks that are waiting
+    /// for the message.
+    recv_wakers: Wakers,
+    /// The waker of the sender's task if it is waiting for
+    /// the channel to be cancelled.
+    send_wakers: Wakers,
+}
+
+struct State<D> {
+    /// The message that has been sent over the channel.
+    data: Option<D>,
+    sender: SenderState,
+    /// The number of receivers that haven't been closed
+    /// or dropped.
+    receivers: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SenderState {
+    /// The sender hasn't sent a message yet.
+    Pending,
+    Sent,
+    /// The sender has been closed without sending a
+    /// message.
+    Closed,
+    /// The sender has been dropped without sending a
+    /// message.
+    Dropped,
+    /// The sender has been dropped without sending a
+    /// message while its thread was panicking.
+    Panicked,
+}
+
+impl<D> Sender<D> {
+    /// Sends `data` over the channel, returning `Ok(())` if it
+    /// has been successfully sent or either
+    /// `Err(SendError::Closed)` if the channel has been cancelled
+    /// or `Err(SendError::Full)` if a message has already been
+    /// sent over it.
+    pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
+        if self.sent {
+            return Err(SendError::Full(data));
+        } else if self.cancelled || self.closed {
+            return Err(SendError::Closed(data));
+        }
+
+        let mut state = self.inner.lock();
+        if state.receivers == 0 {
+            self.cancelled = true;
+            return Err(SendError::Closed(data));
+        }
+
+        state.data = Some(data);
+        state.sender = SenderState::Sent;
+        drop(state);
+
+        self.sent = true;
+        self.inner.recv_wakers.wake_all();
+        Ok(())
+    }
+
+    /// Whether all the receivers have been closed or
+    /// dropped before a message could be sent over the
+    /// channel.
+    pub fn is_cancelled(&self) -> bool {
+        if self.sent || self.closed || self.cancelled {
+            self.cancelled
+        } else {
+            self.inner.lock().receivers == 0
+        }
+    }
+
+    /// Returns `Poll::Ready(())` if all the receivers have
+    /// been closed or dropped (or if the sender can't send
+    /// a message anymore), or registers the task to be
+    /// woken up when they are.
+    pub fn poll_cancelled(&mut self, cx: &mut Context) -> Poll<()> {
+        if self.sent || self.closed || self.cancelled {
+            return Poll::Ready(());
+        } else if self.is_cancelled() {
+            self.cancelled = true;
+            return Poll::Ready(());
+        }
+
+        self.inner.send_wakers.register(cx.waker());
+
+        if self.is_cancelled() {
+            self.cancelled = true;
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Returns a future that resolves once all the
+    /// receivers have been closed or dropped (see
+    /// [`poll_cancelled`]).
+    ///
+    /// [`poll_cancelled`]: #method.poll_cancelled
+    pub fn cancelled(&mut self) -> CancelledFuture<'_, D> {
+        CancelledFuture { sender: self }
+    }
+
+    /// Tries to close the channel without sending a message
+    /// over it, returning `Ok(())` if it succeeded or
+    /// `Err(CloseError::Closed)` if a message has already been
+    /// sent over the channel or if it has already been
+    /// cancelled or closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.sent || self.closed || self.cancelled {
+            return Err(CloseError::Closed);
+        }
+
+        self.inner.lock().sender = SenderState::Closed;
+        self.closed = true;
+
+        self.inner.recv_wakers.wake_all();
+        Ok(())
+    }
+}
+
+impl<D: Clone> Receiver<D> {
+    /// Tries to receive a clone of the message sent over
+    /// the channel, returning `Ok(D)` if it has received
+    /// one and either `Err(ReceiveError::Empty)` if it
+    /// hasn't, `Err(ReceiveError::SenderDropped)` if the
+    /// sender has been dropped without sending one,
+    /// `Err(ReceiveError::SenderPanicked)` if it has been
+    /// dropped while its thread was panicking or
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// cancelled or closed (or if the receiver has already
+    /// received the message).
+    pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
+        if self.received || self.closed {
+            return Err(ReceiveError::Closed);
+        }
+
+        let state = self.inner.lock();
+        let err = match state.sender {
+            SenderState::Pending => return Err(ReceiveError::Empty),
+            SenderState::Sent => {
+                let data = state.data.clone().unwrap();
+                self.received = true;
+                return Ok(data);
+            }
+            SenderState::Closed => ReceiveError::Closed,
+            SenderState::Dropped => ReceiveError::SenderDropped,
+            SenderState::Panicked => ReceiveError::SenderPanicked,
+        };
+
+        self.cancelled = true;
+        Err(err)
+    }
+
+    /// Blocks the current thread until a message has been
+    /// received over the channel, returning `Ok(D)` if one
+    /// has been, or the error that [`try_recv`] would have
+    /// returned if the channel has been cancelled or closed.
+    ///
+    /// [`try_recv`]: #method.try_recv
+    pub fn wait(&mut self) -> Result<D, ReceiveError> {
+        blocking::wait(|cx| Pin::new(&mut *self).poll(cx))
+    }
+
+    /// Blocks the current thread until a message has been
+    /// received over the channel (see [`wait`]), for at
+    /// most `timeout`, returning `Err(ReceiveError::Timeout)`
+    /// if none has been in time.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::wait_until(Instant::now() + timeout, |cx| Pin::new(&mut *self).poll(cx))
+            .unwrap_or(Err(ReceiveError::Timeout))
+    }
+}
+
+impl<D> Receiver<D> {
+    /// Tries to close the receiver, returning `Ok(())` if
+    /// it succeeded or `Err(CloseError::Closed)` it the
+    /// receiver has already been closed.
+    ///
+    /// The channel is cancelled once all the receivers have
+    /// been closed or dropped.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.closed {
+            Err(CloseError::Closed)
+        } else {
+            self.closed = true;
+            self.inner.remove_receiver();
+            Ok(())
+        }
+    }
+}
+
+impl<D> Inner<D> {
+    fn lock(&self) -> MutexGuard<'_, State<D>> {
+        // NOTE: the state is only poisoned if cloning the
+        //   message panicked, which leaves it unchanged (and
+        //   the receivers and sender lock it when dropped,
+        //   which can happen while their thread is panicking).
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Unregisters a receiver, waking up the sender if it
+    /// was the last one.
+    fn remove_receiver(&self) {
+        let mut state = self.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            drop(state);
+            self.send_wakers.wake_all();
+        }
+    }
+}
+
+impl<D> Unpin for Sender<D> {}
+impl<D> Unpin for Receiver<D> {}
+impl<D> Unpin for CancelledFuture<'_, D> {}
+
+impl<D> Future for CancelledFuture<'_, D> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        self.get_mut().sender.poll_cancelled(cx)
+    }
+}
+
+impl<D: Clone> Future for Receiver<D> {
+    type Output = Result<D, ReceiveError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        let receiver = self.get_mut();
+        match receiver.try_recv() {
+            Err(ReceiveError::Empty) => (),
+            res => return Poll::Ready(res),
+        }
+
+        receiver.inner.recv_wakers.register(cx.waker());
+
+        match receiver.try_recv() {
+            Err(ReceiveError::Empty) => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+}
+
+impl<D: Clone> Clone for Receiver<D> {
+    /// Returns a new receiver, which will receive the
+    /// message sent over the channel even if it has
+    /// already been sent.
+    fn clone(&self) -> Receiver<D> {
+        self.inner.lock().receivers += 1;
+
+        Receiver {
+            received: false,
+            closed: false,
+            cancelled: self.cancelled,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<D> Drop for Sender<D> {
+    fn drop(&mut self) {
+        if self.sent || self.closed || self.cancelled {
+            return;
+        }
+
+        self.inner.lock().sender = if thread::panicking() {
+            SenderState::Panicked
+        } else {
+            SenderState::Dropped
+        };
+
+        self.inner.recv_wakers.wake_all();
+    }
+}
+
+impl<D> Drop for Receiver<D> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.inner.remove_receiver();
+        }
+    }
+}
+
+impl<D> fmt::Debug for Inner<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("Inner")
+            .field("sender", &state.sender)
+            .field("receivers", &state.receivers)
+            .finish()
+    }
+}
@@ -0,0 +1,231 @@
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::AtomicPtr;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+/// A lock-free, multi-producer, single-consumer linked
+/// queue (based on Dmitry Vyukov's intrusive MPSC queue).
+///
+/// The values pushed to the queue embed the link to the
+/// next value (see [`Linked`]), so that pushing a boxed
+/// value doesn't allocate, and popping it gives back the
+/// same box. The queue embeds its own stub link, which
+/// is pushed back whenever the queue would otherwise be
+/// emptied.
+///
+/// Pushing data is wait-free, while popping it might
+/// have to spin if a producer is in the middle of
+/// linking a new value.
+///
+/// [`Linked`]: trait.Linked.html
+pub(crate) struct Queue<T: Linked> {
+    /// The link of the value that was pushed last (or a
+    /// null pointer standing for the stub, until the stub
+    /// has been pushed back).
+    head: AtomicPtr<Link>,
+    /// The link of the value that will be popped next (or
+    /// a null pointer standing for the stub, until the
+    /// stub has been pushed back), only accessed by the
+    /// consumer.
+    tail: UnsafeCell<*mut Link>,
+    stub: Link,
+    _values: PhantomData<Box<T>>,
+}
+
+/// The link to the next value of a [`Queue`], embedded in
+/// the values pushed to it.
+///
+/// [`Queue`]: struct.Queue.html
+pub(crate) struct Link {
+    next: AtomicPtr<Link>,
+}
+
+/// A value that can be pushed to a [`Queue`].
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]` and have a [`Link`] as
+/// its first field, so that a pointer to a value is also
+/// a pointer to its link.
+///
+/// [`Queue`]: struct.Queue.html
+/// [`Link`]: struct.Link.html
+pub(crate) unsafe trait Linked {}
+
+enum Pop<T> {
+    Data(Box<T>),
+    Empty,
+    /// A producer swapped the head but didn't link the
+    /// previous value to the new one yet.
+    Inconsistent,
+}
+
+impl<T: Linked> Queue<T> {
+    pub(crate) fn new() -> Queue<T> {
+        // NOTE: the stub's address is only stored once it is
+        //   pushed back by the consumer, which can't happen
+        //   before the queue has been shared (and thus can't
+        //   be moved anymore).
+        Queue {
+            head: AtomicPtr::new(ptr::null_mut()),
+            tail: UnsafeCell::new(ptr::null_mut()),
+            stub: Link::new(),
+            _values: PhantomData,
+        }
+    }
+
+    /// Pushes `value` at the back of the queue.
+    pub(crate) fn push(&self, value: Box<T>) {
+        // SAFETY: `value` has just been unboxed.
+        unsafe { self.push_link(Box::into_raw(value) as *mut Link) }
+    }
+
+    /// Pushes `value` at the back of the queue, without
+    /// synchronizing with other producers.
+    ///
+    /// # Safety
+    ///
+    /// There can only be one producer pushing data to
+    /// the queue (for its whole lifetime).
+    pub(crate) unsafe fn push_single(&self, value: Box<T>) {
+        let link = Box::into_raw(value) as *mut Link;
+
+        // NOTE: the head is only ever written by the
+        //   producer (or by the consumer while the queue is
+        //   empty, after the producer is done pushing), so it
+        //   doesn't have to be swapped.
+        let prev = self.resolve(self.head.load(Ordering::Acquire));
+        self.head.store(link, Ordering::Release);
+        (*prev).next.store(link, Ordering::Release);
+    }
+
+    /// Pops the value at the front of the queue, if any.
+    ///
+    /// # Safety
+    ///
+    /// This method must never be called concurrently
+    /// (there can only be one consumer at a time), and the
+    /// queue must not be moved afterwards (since it might
+    /// have pushed its stub back).
+    pub(crate) unsafe fn pop(&self) -> Option<Box<T>> {
+        loop {
+            match self.try_pop() {
+                Pop::Data(value) => return Some(value),
+                Pop::Empty => return None,
+                Pop::Inconsistent => thread::yield_now(),
+            }
+        }
+    }
+
+    unsafe fn try_pop(&self) -> Pop<T> {
+        let stub = self.stub();
+        let mut tail = self.resolve(*self.tail.get());
+        let mut next = (*tail).next.load(Ordering::Acquire);
+
+        if tail == stub {
+            if next.is_null() {
+                return self.empty_or_inconsistent(tail);
+            }
+
+            // NOTE: the stub is skipped.
+            *self.tail.get() = next;
+            tail = next;
+            next = (*next).next.load(Ordering::Acquire);
+        }
+
+        if !next.is_null() {
+            *self.tail.get() = next;
+            return Pop::Data(Box::from_raw(tail as *mut T));
+        }
+
+        // NOTE: `tail` is the last value that has been linked,
+        //   and it can only be popped once another link has
+        //   been linked after it, so the stub is pushed back.
+        if self.resolve(self.head.load(Ordering::Acquire)) != tail {
+            return Pop::Inconsistent;
+        }
+
+        self.push_link(stub);
+
+        next = (*tail).next.load(Ordering::Acquire);
+        if !next.is_null() {
+            *self.tail.get() = next;
+            Pop::Data(Box::from_raw(tail as *mut T))
+        } else {
+            Pop::Inconsistent
+        }
+    }
+
+    /// Whether the queue, whose tail is `tail` and has no
+    /// next link, is empty or has a producer in the middle
+    /// of linking a new value.
+    fn empty_or_inconsistent(&self, tail: *mut Link) -> Pop<T> {
+        if self.resolve(self.head.load(Ordering::Acquire)) == tail {
+            Pop::Empty
+        } else {
+            Pop::Inconsistent
+        }
+    }
+
+    /// Pushes `link` at the back of the queue.
+    ///
+    /// # Safety
+    ///
+    /// `link` must either be the stub or the link of a
+    /// value that has been unboxed, which isn't in the
+    /// queue.
+    unsafe fn push_link(&self, link: *mut Link) {
+        (*link).next.store(ptr::null_mut(), Ordering::Relaxed);
+
+        let prev = self.resolve(self.head.swap(link, Ordering::AcqRel));
+        // SAFETY: `prev` is either the stub or the link of a
+        //   value that has been pushed but can't have been
+        //   popped (and thus freed) yet, since it isn't linked.
+        (*prev).next.store(link, Ordering::Release);
+    }
+
+    fn stub(&self) -> *mut Link {
+        &self.stub as *const Link as *mut Link
+    }
+
+    /// Returns the stub if `link` is null, or `link`
+    /// itself otherwise.
+    fn resolve(&self, link: *mut Link) -> *mut Link {
+        if link.is_null() {
+            self.stub()
+        } else {
+            link
+        }
+    }
+}
+
+impl Link {
+    pub(crate) fn new() -> Link {
+        Link {
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+unsafe impl<T: Linked + Send> Send for Queue<T> {}
+unsafe impl<T: Linked + Send> Sync for Queue<T> {}
+
+impl<T: Linked> Drop for Queue<T> {
+    fn drop(&mut self) {
+        let stub = self.stub();
+        let tail = *self.tail.get_mut();
+        let mut link = self.resolve(tail);
+        while !link.is_null() {
+            // SAFETY: we have an exclusive access to the
+            //   queue and every value is only owned by it.
+            let next = unsafe { (*link).next.load(Ordering::Relaxed) };
+            if link != stub {
+                drop(unsafe { Box::from_raw(link as *mut T) });
+            }
+
+            link = next;
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use futures_sink::Sink;
+
+use crate::error::*;
+use crate::inner;
+use crate::inner::Inner;
+use crate::receipt::Receipt;
+
+pub use crate::unbounded::Receiver;
+pub use crate::unbounded::RecvFuture;
+pub use crate::unbounded::RecvTimeoutFuture;
+
+/// Creates a new channel over which at most `quota`
+/// messages can be sent, and which is closed once the
+/// last one has been sent (a [`once`] channel being
+/// a channel with a quota of one message).
+///
+/// [`once`]: ../once/index.html
+pub fn new<D>(quota: usize) -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(None).with_quota(quota));
+
+    (Sender::new(quota, inner.clone()), Receiver::new(inner))
+}
+
+#[derive(Debug)]
+/// The sending half of a channel with a quota of
+/// messages, which shares the channel's state (and its
+/// quota) with the other senders and the receiver.
+pub struct Sender<D> {
+    /// The total number of messages that can be sent
+    /// over the channel (as it was provided to [`new`]).
+    ///
+    /// [`new`]: fn.new.html
+    pub quota: usize,
+    sender: inner::Sender<D>,
+}
+
+impl<D> Sender<D> {
+    pub(crate) fn new(quota: usize, inner: Arc<Inner<D>>) -> Sender<D> {
+        Sender {
+            quota,
+            sender: inner::Sender::new(inner),
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the senders, by the receiver or because its
+    /// quota has been exhausted).
+    pub fn is_closed(&self) -> bool {
+        self.sender.inner().is_closed()
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.sender.is_disconnected()
+    }
+
+    /// The number of messages that can still be sent over
+    /// the channel (by any of the senders).
+    pub fn remaining(&self) -> usize {
+        self.sender.inner().remaining().unwrap()
+    }
+
+    /// Sends `data` over the channel, returning `Ok(())` if
+    /// it has been successfully sent, or either
+    /// `Err(SendError::Exhausted)` if the channel's quota has
+    /// been exhausted, `Err(SendError::Disconnected)` if the
+    /// sender has disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been closed.
+    pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
+        self.sender.try_send(data)
+    }
+
+    /// Sends `data` over the channel (see [`send`]),
+    /// returning a [`Receipt`] that resolves once it has
+    /// been received if it has been successfully sent.
+    ///
+    /// [`send`]: #method.send
+    /// [`Receipt`]: ../receipt/struct.Receipt.html
+    pub fn send_notify(&mut self, data: D) -> Result<Receipt, SendError<D>> {
+        self.sender.try_send_notify(data)
+    }
+
+    /// Tries to clone the sender, returning the new sender if
+    /// it succeeded, or `Err(CloneError::Limit)` if the
+    /// channel already has the maximum number of senders
+    /// that it can have.
+    pub fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        Ok(Sender {
+            quota: self.quota,
+            sender: self.sender.try_clone()?,
+        })
+    }
+
+    /// Tries to disconnect the sender from the channel,
+    /// returning `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the sender
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        self.sender.disconnect()
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the sender already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        self.sender.close()
+    }
+}
+
+impl<D> Unpin for Sender<D> {}
+
+impl<D> Sink<D> for Sender<D> {
+    // NOTE: the buffer is never full, so the message is
+    //   sent (or returned in an error) by `start_send`.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        self.get_mut().sender.start_send(msg)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_close(cx)
+    }
+}
+
+impl<D> Clone for Sender<D> {
+    /// # Panics
+    ///
+    /// This method panics if the channel already has the
+    /// maximum number of senders that it can have (see
+    /// [`try_clone`]).
+    ///
+    /// [`try_clone`]: #method.try_clone
+    fn clone(&self) -> Sender<D> {
+        self.try_clone()
+            .expect("the channel already has the maximum number of senders")
+    }
+}
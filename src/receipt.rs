@@ -0,0 +1,65 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use futures_core::future::Future;
+
+use crate::error::*;
+use crate::once;
+
+/// Creates a new read receipt, along with the notifier
+/// that has to be sent with the message it is the
+/// receipt of.
+pub(crate) fn new() -> (Notifier, Receipt) {
+    let (sender, receiver) = once::new();
+
+    (Notifier { sender }, Receipt { receiver })
+}
+
+#[derive(Debug)]
+/// A future that resolves when the message it is the
+/// receipt of has been received, resolving to `Ok(())`
+/// if it has been, or to `Err(ReceiveError::Closed)` if
+/// it has been dropped without being received (e.g.
+/// because the channel's receiver has been dropped).
+pub struct Receipt {
+    receiver: once::Receiver<()>,
+}
+
+#[derive(Debug)]
+/// The notifier of a [`Receipt`], which is stored along
+/// with the message it is the receipt of, and notifies
+/// it when the message is received, or when it is
+/// dropped.
+///
+/// [`Receipt`]: struct.Receipt.html
+pub(crate) struct Notifier {
+    sender: once::Sender<()>,
+}
+
+impl Notifier {
+    /// Notifies the receipt that the message has been
+    /// received.
+    pub(crate) fn notify(mut self) {
+        // NOTE: the receipt might have been dropped, in
+        //   which case nobody is waiting to be notified.
+        let _ = self.sender.send(());
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        // NOTE: the notifier is closed rather than dropped
+        //   (unless it has already been notified), so that
+        //   the receipt resolves to `Err(ReceiveError::Closed)`.
+        let _ = self.sender.close();
+    }
+}
+
+impl Future for Receipt {
+    type Output = Result<(), ReceiveError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), ReceiveError>> {
+        Pin::new(&mut self.get_mut().receiver).poll(cx)
+    }
+}
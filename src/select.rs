@@ -0,0 +1,416 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use futures_core::future::Future;
+use futures_core::stream::FusedStream;
+use futures_core::stream::Stream;
+
+use crate::blocking;
+use crate::bounded;
+use crate::broadcast;
+use crate::channel;
+use crate::error::*;
+use crate::mpmc;
+use crate::once;
+use crate::priority;
+use crate::promise;
+use crate::unbounded;
+
+/// A receiver that can be waited on along with other
+/// receivers (see [`Select`]).
+///
+/// [`Select`]: struct.Select.html
+pub trait Selectable {
+    /// The type of the messages that the receiver
+    /// receives.
+    type Item;
+
+    /// Tries to receive a message, registering the task
+    /// to be woken up when one is sent if there is none
+    /// available.
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<Self::Item, ReceiveError>>;
+
+    /// Whether the receiver won't receive any message
+    /// anymore.
+    fn is_done(&self) -> bool;
+
+    /// Lets the receiver know that the select isn't
+    /// polling it anymore (because another branch has been
+    /// selected, or because the select has been dropped).
+    fn abandon(&mut self) {}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// The order in which a [`Select`] checks its branches
+/// when more than one of them might be ready.
+///
+/// [`Select`]: struct.Select.html
+pub enum Fairness {
+    /// Starts with a randomly chosen branch.
+    Random,
+    /// Starts with the branch following the one that was
+    /// selected last.
+    RoundRobin,
+    /// Always starts with the first branch, so that the
+    /// branches that were added first have priority over
+    /// the others.
+    Biased,
+}
+
+/// A set of receivers (its branches) that can be waited
+/// on at the same time, resolving with the index of the
+/// first branch that receives a message (or fails to),
+/// along with the message or error.
+///
+/// The messages of each branch are mapped to a common
+/// type `O`. Branches are indexed in the order in which
+/// they have been added, and stop being polled once
+/// they won't receive any message anymore (after the
+/// select has resolved with their last message or
+/// error).
+pub struct Select<'a, O> {
+    fairness: Fairness,
+    branches: Vec<Branch<'a, O>>,
+    /// The branch that the select starts with if its
+    /// fairness is `Fairness::RoundRobin`.
+    next: usize,
+    /// The state of the random number generator used if
+    /// the select's fairness is `Fairness::Random`.
+    rng: u64,
+}
+
+#[derive(Debug)]
+/// A future that resolves with the first branch of a
+/// [`Select`] that receives a message (see
+/// [`Select::select`]).
+///
+/// [`Select`]: struct.Select.html
+/// [`Select::select`]: struct.Select.html#method.select
+pub struct SelectFuture<'s, 'a, O> {
+    select: &'s mut Select<'a, O>,
+}
+
+struct Branch<'a, O> {
+    receiver: Box<dyn Receiver<O> + 'a>,
+    /// Whether the branch won't receive any message
+    /// anymore.
+    done: bool,
+}
+
+/// A receiver along with the function mapping its
+/// messages to the select's output type.
+struct Mapped<'a, S, F> {
+    receiver: &'a mut S,
+    map: F,
+}
+
+/// A [`Selectable`] whose messages have been mapped to
+/// the select's output type.
+///
+/// [`Selectable`]: trait.Selectable.html
+trait Receiver<O> {
+    fn poll(&mut self, cx: &mut Context) -> Poll<Result<O, ReceiveError>>;
+
+    fn is_done(&self) -> bool;
+
+    fn abandon(&mut self);
+}
+
+impl<'a, O> Select<'a, O> {
+    /// Creates a new select without any branch, which
+    /// checks its branches in the order defined by
+    /// `fairness`.
+    pub fn new(fairness: Fairness) -> Select<'a, O> {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(0);
+
+        Select {
+            fairness,
+            branches: vec![],
+            next: 0,
+            // NOTE: the generator's state can't be zero.
+            rng: hasher.finish() | 1,
+        }
+    }
+
+    /// Adds a branch receiving messages over `receiver`
+    /// and mapping them with `map`.
+    pub fn recv<S, F>(mut self, receiver: &'a mut S, map: F) -> Select<'a, O>
+    where
+        S: Selectable,
+        F: FnMut(S::Item) -> O + 'a,
+    {
+        self.branches.push(Branch {
+            done: receiver.is_done(),
+            receiver: Box::new(Mapped { receiver, map }),
+        });
+
+        self
+    }
+
+    /// The number of branches that can still receive
+    /// messages.
+    pub fn remaining(&self) -> usize {
+        self.branches.iter().filter(|branch| !branch.done).count()
+    }
+
+    /// Returns a future that resolves with the index of
+    /// the first branch that receives a message (or fails
+    /// to), along with the message or error, or with
+    /// `None` if none of the branches can receive messages
+    /// anymore.
+    pub fn select(&mut self) -> SelectFuture<'_, 'a, O> {
+        SelectFuture { select: self }
+    }
+
+    /// Waits for a branch to receive a message (see
+    /// [`select`]), blocking the current thread until one
+    /// does.
+    ///
+    /// [`select`]: #method.select
+    pub fn select_blocking(&mut self) -> Option<(usize, Result<O, ReceiveError>)> {
+        blocking::wait(|cx| self.poll_select(cx))
+    }
+
+    /// Abandons all the branches except `selected` (see
+    /// [`Selectable::abandon`]).
+    ///
+    /// [`Selectable::abandon`]: trait.Selectable.html#method.abandon
+    fn abandon(&mut self, selected: Option<usize>) {
+        for (index, branch) in self.branches.iter_mut().enumerate() {
+            if Some(index) != selected && !branch.done {
+                branch.receiver.abandon();
+            }
+        }
+    }
+
+    /// Polls the branches in the order defined by the
+    /// select's fairness, stopping at the first one that
+    /// is ready.
+    pub(crate) fn poll_select(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<Option<(usize, Result<O, ReceiveError>)>> {
+        let len = self.branches.len();
+        if self.remaining() == 0 {
+            return Poll::Ready(None);
+        }
+
+        let start = match self.fairness {
+            Fairness::Random => self.random() % len,
+            Fairness::RoundRobin => self.next,
+            Fairness::Biased => 0,
+        };
+
+        for i in 0..len {
+            let index = (start + i) % len;
+            let branch = &mut self.branches[index];
+            if branch.done {
+                continue;
+            }
+
+            if let Poll::Ready(res) = branch.receiver.poll(cx) {
+                branch.done = branch.receiver.is_done();
+                self.next = (index + 1) % len;
+                self.abandon(Some(index));
+
+                return Poll::Ready(Some((index, res)));
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// Returns the next number generated by a xorshift
+    /// generator.
+    fn random(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+
+        self.rng as usize
+    }
+}
+
+impl<S, F, O> Receiver<O> for Mapped<'_, S, F>
+where
+    S: Selectable,
+    F: FnMut(S::Item) -> O,
+{
+    fn poll(&mut self, cx: &mut Context) -> Poll<Result<O, ReceiveError>> {
+        match self.receiver.poll_select(cx) {
+            Poll::Ready(res) => Poll::Ready(res.map(&mut self.map)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.receiver.is_done()
+    }
+
+    fn abandon(&mut self) {
+        self.receiver.abandon();
+    }
+}
+
+impl<D> Selectable for bounded::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.poll_recv(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_terminated()
+    }
+
+    fn abandon(&mut self) {
+        self.stop_waiting();
+    }
+}
+
+impl<D> Selectable for unbounded::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.poll_recv(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_terminated()
+    }
+}
+
+impl<D> Selectable for mpmc::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.poll_recv(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_terminated()
+    }
+}
+
+impl<D> Selectable for broadcast::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.poll_recv(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_terminated()
+    }
+}
+
+impl<D> Selectable for priority::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.poll_recv(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_terminated()
+    }
+}
+
+impl<D> Selectable for channel::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.poll_recv(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_terminated()
+    }
+
+    fn abandon(&mut self) {
+        self.stop_waiting();
+    }
+}
+
+impl<D> Selectable for once::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        Pin::new(self).poll(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.received || self.closed || self.cancelled
+    }
+}
+
+impl<D: Clone> Selectable for promise::Receiver<D> {
+    type Item = D;
+
+    fn poll_select(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        Pin::new(self).poll(cx)
+    }
+
+    fn is_done(&self) -> bool {
+        self.received || self.closed || self.cancelled
+    }
+}
+
+impl<O> Unpin for Select<'_, O> {}
+impl<O> Unpin for SelectFuture<'_, '_, O> {}
+
+impl<O> Future for SelectFuture<'_, '_, O> {
+    type Output = Option<(usize, Result<O, ReceiveError>)>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<(usize, Result<O, ReceiveError>)>> {
+        self.get_mut().select.poll_select(cx)
+    }
+}
+
+impl<O> Drop for SelectFuture<'_, '_, O> {
+    fn drop(&mut self) {
+        self.select.abandon(None);
+    }
+}
+
+impl<O> Drop for Select<'_, O> {
+    fn drop(&mut self) {
+        self.abandon(None);
+    }
+}
+
+impl<O> FusedStream for Select<'_, O> {
+    fn is_terminated(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+impl<O> Stream for Select<'_, O> {
+    type Item = (usize, Result<O, ReceiveError>);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(usize, Result<O, ReceiveError>)>> {
+        self.get_mut().poll_select(cx)
+    }
+}
+
+impl<O> fmt::Debug for Select<'_, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Select")
+            .field("fairness", &self.fairness)
+            .field("branches", &self.branches.len())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::Weak;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A timer driving the futures returned by the senders'
+/// `send_within` and `send_deadline` methods and by the
+/// receivers' `recv_within` and `recv_deadline` methods.
+///
+/// It can be backed by a runtime's timer, or be either a
+/// [`ThreadTimer`] or a [`ManualTimer`].
+///
+/// [`ThreadTimer`]: struct.ThreadTimer.html
+/// [`ManualTimer`]: struct.ManualTimer.html
+pub trait Timer {
+    /// Returns the current instant, as measured by the
+    /// timer.
+    fn now(&self) -> Instant;
+
+    /// Returns `Poll::Ready(())` if `deadline` has been
+    /// reached, or registers the task to be woken up
+    /// when it is.
+    fn poll_deadline(&self, deadline: Instant, cx: &mut Context) -> Poll<()>;
+
+    /// Unregisters the task of `waker`, which was waiting
+    /// for `deadline` to be reached (because the future
+    /// that was waiting for it has resolved or has been
+    /// dropped).
+    ///
+    /// The default implementation does nothing, letting
+    /// the task be woken up once `deadline` is reached.
+    fn cancel_deadline(&self, _deadline: Instant, _waker: &Waker) {}
+}
+
+/// The handle of the timer shared by the timers created
+/// with `ThreadTimer::default`, while any of them is still
+/// alive.
+static DEFAULT: Mutex<Weak<Handle>> = Mutex::new(Weak::new());
+
+#[derive(Clone)]
+/// A timer backed by a thread that wakes up tasks once
+/// their deadline has been reached, and which stops once
+/// the timer and all its clones have been dropped.
+///
+/// The timers created with `ThreadTimer::default` share a
+/// single thread, while the ones created with
+/// [`new`] each spawn their own.
+///
+/// [`new`]: #method.new
+pub struct ThreadTimer {
+    handle: Arc<Handle>,
+}
+
+#[derive(Clone, Debug)]
+/// A timer whose clock only moves forward when it is
+/// advanced (see [`advance`]), which is useful to test
+/// timeouts deterministically.
+///
+/// [`advance`]: #method.advance
+pub struct ManualTimer {
+    state: Arc<Mutex<ManualState>>,
+}
+
+/// The handle of a [`ThreadTimer`], stopping its thread
+/// when it is dropped.
+///
+/// [`ThreadTimer`]: struct.ThreadTimer.html
+struct Handle {
+    shared: Arc<Shared>,
+}
+
+/// The state shared by a [`ThreadTimer`] and its thread.
+///
+/// [`ThreadTimer`]: struct.ThreadTimer.html
+struct Shared {
+    state: Mutex<ThreadState>,
+    cond: Condvar,
+}
+
+struct ThreadState {
+    /// Whether the timer has been dropped.
+    stopped: bool,
+    deadlines: Deadlines,
+}
+
+#[derive(Debug)]
+struct ManualState {
+    now: Instant,
+    deadlines: Deadlines,
+}
+
+#[derive(Debug, Default)]
+/// The deadlines registered by tasks, along with the
+/// wakers of these tasks.
+struct Deadlines {
+    wakers: Vec<(Instant, Waker)>,
+}
+
+#[derive(Debug)]
+/// A deadline that a future is waiting for, which
+/// unregisters the future's task from the timer when it
+/// is dropped.
+pub(crate) struct Delay<T: Timer> {
+    deadline: Instant,
+    timer: T,
+    /// The waker of the task that is registered to be
+    /// woken up once the deadline is reached, if any.
+    waker: Option<Waker>,
+}
+
+impl<T: Timer + ?Sized> Timer for &T {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn poll_deadline(&self, deadline: Instant, cx: &mut Context) -> Poll<()> {
+        (**self).poll_deadline(deadline, cx)
+    }
+
+    fn cancel_deadline(&self, deadline: Instant, waker: &Waker) {
+        (**self).cancel_deadline(deadline, waker)
+    }
+}
+
+impl<T: Timer> Delay<T> {
+    pub(crate) fn new(deadline: Instant, timer: T) -> Delay<T> {
+        Delay {
+            deadline,
+            timer,
+            waker: None,
+        }
+    }
+
+    /// Returns `Poll::Ready(())` if the deadline has been
+    /// reached, or registers the task to be woken up when
+    /// it is (see [`Timer::poll_deadline`]).
+    ///
+    /// [`Timer::poll_deadline`]: trait.Timer.html#tymethod.poll_deadline
+    pub(crate) fn poll(&mut self, cx: &mut Context) -> Poll<()> {
+        match self.waker {
+            Some(ref waker) if waker.will_wake(cx.waker()) => (),
+            _ => self.cancel(),
+        }
+
+        match self.timer.poll_deadline(self.deadline, cx) {
+            Poll::Ready(()) => {
+                self.waker = None;
+                Poll::Ready(())
+            }
+            Poll::Pending => {
+                if self.waker.is_none() {
+                    self.waker = Some(cx.waker().clone());
+                }
+
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Unregisters the task from the timer, if it is
+    /// registered.
+    fn cancel(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            self.timer.cancel_deadline(self.deadline, &waker);
+        }
+    }
+}
+
+impl ThreadTimer {
+    /// Creates a new timer, spawning the thread backing
+    /// it (see `ThreadTimer::default` to share a timer
+    /// instead).
+    pub fn new() -> ThreadTimer {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(ThreadState {
+                stopped: false,
+                deadlines: Deadlines::default(),
+            }),
+            cond: Condvar::new(),
+        });
+
+        let thread = shared.clone();
+        thread::Builder::new()
+            .name("aktoro-channel-timer".into())
+            .spawn(move || thread.run())
+            .expect("failed to spawn the timer's thread");
+
+        ThreadTimer {
+            handle: Arc::new(Handle { shared }),
+        }
+    }
+}
+
+impl ManualTimer {
+    /// Creates a new timer whose clock starts at the
+    /// current instant.
+    pub fn new() -> ManualTimer {
+        ManualTimer {
+            state: Arc::new(Mutex::new(ManualState {
+                now: Instant::now(),
+                deadlines: Deadlines::default(),
+            })),
+        }
+    }
+
+    /// Moves the timer's clock forward by `duration`,
+    /// waking up the tasks whose deadline has been
+    /// reached.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.lock();
+        state.now += duration;
+
+        let now = state.now;
+        let elapsed = state.deadlines.take_elapsed(now);
+        drop(state);
+
+        for waker in elapsed {
+            waker.wake();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ManualState> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl Shared {
+    /// Wakes up the tasks whose deadline has been reached
+    /// until the timer is dropped.
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.stopped {
+                return;
+            }
+
+            let now = Instant::now();
+            let elapsed = state.deadlines.take_elapsed(now);
+            if !elapsed.is_empty() {
+                // NOTE: the tasks are woken up without holding
+                //   the lock, since they might poll their
+                //   futures right away.
+                drop(state);
+                for waker in elapsed {
+                    waker.wake();
+                }
+
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            state = match state.deadlines.next() {
+                Some(next) => self.cond.wait_timeout(state, next - now).unwrap().0,
+                None => self.cond.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+impl Deadlines {
+    /// Registers the task to be woken up once `deadline`
+    /// has been reached, if it isn't already.
+    fn register(&mut self, deadline: Instant, waker: &Waker) {
+        if !self
+            .wakers
+            .iter()
+            .any(|(d, w)| *d == deadline && w.will_wake(waker))
+        {
+            self.wakers.push((deadline, waker.clone()));
+        }
+    }
+
+    /// Unregisters the task of `waker` from being woken
+    /// up once `deadline` has been reached.
+    fn cancel(&mut self, deadline: Instant, waker: &Waker) {
+        self.wakers
+            .retain(|(d, w)| *d != deadline || !w.will_wake(waker));
+    }
+
+    /// Unregisters the tasks whose deadline has been
+    /// reached, returning their wakers.
+    fn take_elapsed(&mut self, now: Instant) -> Vec<Waker> {
+        let mut elapsed = vec![];
+        let mut i = 0;
+        while i < self.wakers.len() {
+            if self.wakers[i].0 <= now {
+                elapsed.push(self.wakers.swap_remove(i).1);
+            } else {
+                i += 1;
+            }
+        }
+
+        elapsed
+    }
+
+    /// Returns the earliest registered deadline.
+    fn next(&self) -> Option<Instant> {
+        self.wakers.iter().map(|(deadline, _)| *deadline).min()
+    }
+}
+
+impl Timer for ThreadTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn poll_deadline(&self, deadline: Instant, cx: &mut Context) -> Poll<()> {
+        if Instant::now() >= deadline {
+            return Poll::Ready(());
+        }
+
+        let shared = &self.handle.shared;
+        let mut state = shared.state.lock().unwrap();
+
+        // NOTE: the thread only needs to be notified if it is
+        //   waiting for a later deadline.
+        let earliest = match state.deadlines.next() {
+            Some(next) => deadline < next,
+            None => true,
+        };
+        state.deadlines.register(deadline, cx.waker());
+        drop(state);
+
+        if earliest {
+            shared.cond.notify_one();
+        }
+
+        Poll::Pending
+    }
+
+    fn cancel_deadline(&self, deadline: Instant, waker: &Waker) {
+        // NOTE: the thread isn't notified; if it is waiting for
+        //   `deadline`, it will wake up once, find nothing to
+        //   wake, and wait for the next deadline.
+        let shared = &self.handle.shared;
+        shared
+            .state
+            .lock()
+            .unwrap()
+            .deadlines
+            .cancel(deadline, waker);
+    }
+}
+
+impl Timer for ManualTimer {
+    fn now(&self) -> Instant {
+        self.lock().now
+    }
+
+    fn poll_deadline(&self, deadline: Instant, cx: &mut Context) -> Poll<()> {
+        let mut state = self.lock();
+        if state.now >= deadline {
+            Poll::Ready(())
+        } else {
+            state.deadlines.register(deadline, cx.waker());
+            Poll::Pending
+        }
+    }
+
+    fn cancel_deadline(&self, deadline: Instant, waker: &Waker) {
+        self.lock().deadlines.cancel(deadline, waker);
+    }
+}
+
+impl Default for ThreadTimer {
+    /// Returns a timer sharing its thread with the other
+    /// timers created by this method, spawning it if none
+    /// of them is still alive.
+    fn default() -> ThreadTimer {
+        let mut default = DEFAULT.lock().unwrap();
+        if let Some(handle) = default.upgrade() {
+            return ThreadTimer { handle };
+        }
+
+        let timer = ThreadTimer::new();
+        *default = Arc::downgrade(&timer.handle);
+
+        timer
+    }
+}
+
+impl Default for ManualTimer {
+    fn default() -> ManualTimer {
+        ManualTimer::new()
+    }
+}
+
+impl<T: Timer> Drop for Delay<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().stopped = true;
+        self.shared.cond.notify_one();
+    }
+}
+
+impl fmt::Debug for ThreadTimer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.handle.shared.state.lock().unwrap();
+
+        f.debug_struct("ThreadTimer")
+            .field("deadlines", &state.deadlines.wakers.len())
+            .finish()
+    }
+}
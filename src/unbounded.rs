@@ -1,88 +1,121 @@
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 
-use futures_channel::mpsc;
-use futures_channel::mpsc::UnboundedReceiver as FutReceiver;
-use futures_channel::mpsc::UnboundedSender as FutSender;
 use futures_core::stream::FusedStream;
 use futures_core::stream::Stream;
 use futures_sink::Sink;
 
+use crate::blocking;
 use crate::error::*;
+use crate::future;
+use crate::inner;
+use crate::inner::Inner;
+use crate::receipt::Receipt;
+use crate::timer::Timer;
 
-/// Creates a new unbounded channel (see [`futures-channel`'s
-/// documentation]).
-///
-/// [`futures-channel`'s documentation]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/mpsc/fn.unbounded.html
+/// Creates a new unbounded channel.
 pub fn new<D>() -> (Sender<D>, Receiver<D>) {
-    let (sender, receiver) = mpsc::unbounded();
+    let inner = Arc::new(Inner::new(None));
 
-    (Sender::new(sender), Receiver::new(receiver))
+    (Sender::new(inner.clone()), Receiver::new(inner))
 }
 
-#[derive(Debug)]
-/// A wrapper around a [`futures::mpsc::Sender`] that
-/// stores its state after sending data, closing the
-/// channel or disconnecting itself.
+/// Creates a new unbounded channel (see [`new`]), which
+/// can have at most `max_senders` senders at the same
+/// time.
+///
+/// If `max_senders` is `1`, the channel uses a faster
+/// single-producer queue.
+///
+/// # Panics
 ///
-/// [`futures::mpsc::Sender`]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/mpsc/struct.Sender.html
+/// This function panics if `max_senders` is `0`.
+///
+/// [`new`]: fn.new.html
+pub fn with_max_senders<D>(max_senders: usize) -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(None).with_limits(Some(max_senders), Some(1)));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+#[derive(Debug)]
+/// The sending half of an unbounded channel, which shares
+/// the channel's state with the other senders and the
+/// receiver.
 pub struct Sender<D> {
-    /// Whether the channel has been closed.
-    pub closed: bool,
-    /// Whether the sender has diconnected itself from the
-    /// channel.
-    pub disconnected: bool,
-    sender: FutSender<D>,
+    sender: inner::Sender<D>,
 }
 
 #[derive(Debug)]
-/// A wrapper arround a [`futures::mpsc::Receiver`] that
-/// stores it state after trying to receive data or closing
-/// the channel.
-///
-/// [`futures::mpsc::Receiver`]: https://docs.rs/futures-channel-preview/0.3.0-alpha.16/futures_channel/mpsc/struct.Receiver.html
+/// The receiving half of an unbounded channel, which
+/// shares the channel's state with the senders.
 pub struct Receiver<D> {
-    /// Whether the channel has been closed.
-    pub closed: bool,
-    receiver: Option<FutReceiver<D>>,
+    receiver: inner::Receiver<D>,
 }
 
+/// A future that receives a message over an unbounded
+/// channel (see [`Receiver::recv`]).
+///
+/// [`Receiver::recv`]: struct.Receiver.html#method.recv
+pub type RecvFuture<'r, D> = future::RecvFuture<'r, Receiver<D>>;
+
+/// A future that receives a message over an unbounded
+/// channel, unless its deadline is reached first (see
+/// [`Receiver::recv_within`]).
+///
+/// [`Receiver::recv_within`]: struct.Receiver.html#method.recv_within
+pub type RecvTimeoutFuture<'r, D, T> = future::RecvTimeoutFuture<'r, Receiver<D>, T>;
+
 impl<D> Sender<D> {
-    pub(crate) fn new(sender: FutSender<D>) -> Sender<D> {
+    pub(crate) fn new(inner: Arc<Inner<D>>) -> Sender<D> {
         Sender {
-            closed: false,
-            disconnected: false,
-            sender,
+            sender: inner::Sender::new(inner),
         }
     }
 
+    /// Whether the channel has been closed (by any of
+    /// the senders or by the receiver).
+    pub fn is_closed(&self) -> bool {
+        self.sender.inner().is_closed()
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.sender.is_disconnected()
+    }
+
     /// Sends `data` over the channel, returning `Ok(())` if
     /// it has been successfully sent, or either
-    /// `Err(SendError::Disconnected)` if the sender has
+    /// `Err(SendError::Disconnected)` if the  sender has
     /// disconnected itself from the channel or
     /// `Err(SendError::Closed)` if the channel has been closed.
     pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
-        if self.disconnected {
-            return Err(SendError::Disconnected(data));
-        } else if self.closed {
-            return Err(SendError::Closed(data));
-        } else if self.sender.is_closed() {
-            self.closed = true;
-            return Err(SendError::Closed(data));
-        }
+        self.sender.try_send(data)
+    }
 
-        match self.sender.unbounded_send(data) {
-            Ok(()) => Ok(()),
-            Err(err) => {
-                if err.is_disconnected() {
-                    self.closed = true;
-                    Err(SendError::Closed(err.into_inner()))
-                } else {
-                    unreachable!();
-                }
-            }
-        }
+    /// Sends `data` over the channel (see [`send`]),
+    /// returning a [`Receipt`] that resolves once it has
+    /// been received if it has been successfully sent.
+    ///
+    /// [`send`]: #method.send
+    /// [`Receipt`]: ../receipt/struct.Receipt.html
+    pub fn send_notify(&mut self, data: D) -> Result<Receipt, SendError<D>> {
+        self.sender.try_send_notify(data)
+    }
+
+    /// Tries to clone the sender, returning the new sender if
+    /// it succeeded, or `Err(CloneError::Limit)` if the
+    /// channel already has the maximum number of senders
+    /// that it can have.
+    pub fn try_clone(&self) -> Result<Sender<D>, CloneError> {
+        Ok(Sender {
+            sender: self.sender.try_clone()?,
+        })
     }
 
     /// Tries to disconnect the sender from the channel,
@@ -92,18 +125,7 @@ impl<D> Sender<D> {
     /// `Err(DisconnectError::Closed)` if the channel was
     /// already closed.
     pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
-        if self.disconnected {
-            Err(DisconnectError::Disconnected)
-        } else if self.closed {
-            Err(DisconnectError::Closed)
-        } else if self.sender.is_closed() {
-            self.closed = true;
-            Err(DisconnectError::Closed)
-        } else {
-            self.sender.disconnect();
-            self.disconnected = true;
-            Ok(())
-        }
+        self.sender.disconnect()
     }
 
     /// Tries to close the channel, returning `Ok(())` if it
@@ -112,163 +134,181 @@ impl<D> Sender<D> {
     /// `Err(CloseError::Closed)` if the channel was already
     /// closed.
     pub fn close(&mut self) -> Result<(), CloseError> {
-        if self.disconnected {
-            Err(CloseError::Disconnected)
-        } else if self.closed {
-            Err(CloseError::Closed)
-        } else if self.sender.is_closed() {
-            self.closed = true;
-            Err(CloseError::Closed)
-        } else {
-            self.sender.close_channel();
-            self.closed = true;
-            Ok(())
-        }
+        self.sender.close()
     }
 }
 
 impl<D> Receiver<D> {
-    pub(crate) fn new(receiver: FutReceiver<D>) -> Receiver<D> {
+    pub(crate) fn new(inner: Arc<Inner<D>>) -> Receiver<D> {
         Receiver {
-            closed: false,
-            receiver: Some(receiver),
+            receiver: inner::Receiver::new(inner),
         }
     }
 
+    /// Whether the channel has been closed (by the
+    /// receiver or by any of the senders).
+    pub fn is_closed(&self) -> bool {
+        self.receiver.inner().is_closed()
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.receiver.is_disconnected()
+    }
+
     /// Tries to receive a message over the channel, returning
     /// `Ok(D)` if it has received one, and either
-    /// `Err(ReceiveError::Empty)` if it hasn't or
+    /// `Err(ReceiveError::Empty)` if it hasn't,
+    /// `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or
     /// `Err(ReceiveError::Closed)` if the channel has been
     /// closed.
     pub fn try_recv(&mut self) -> Result<D, ReceiveError> {
-        if let Some(ref mut receiver) = self.receiver {
-            match receiver.try_next() {
-                Ok(Some(data)) => Ok(data),
-                Ok(None) => {
-                    self.receiver = None;
-                    self.closed = true;
-                    Err(ReceiveError::Closed)
-                }
-                Err(_) => Err(ReceiveError::Empty),
-            }
-        } else {
-            Err(ReceiveError::Closed)
-        }
+        self.receiver.try_recv()
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel, resolving to `Ok(D)` once it has received
+    /// one, or either to `Err(ReceiveError::Disconnected)`
+    /// if the receiver has disconnected itself from the
+    /// channel or to `Err(ReceiveError::Closed)` if the
+    /// channel has been closed and its buffer is empty.
+    pub fn recv(&mut self) -> RecvFuture<'_, D> {
+        RecvFuture::new(self)
+    }
+
+    /// Receives a message over the channel (see [`recv`]),
+    /// blocking the current thread until there is one
+    /// available.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_blocking(&mut self) -> Result<D, ReceiveError> {
+        blocking::recv(self)
+    }
+
+    /// Receives a message over the channel (see
+    /// [`recv_blocking`]), blocking the current thread for
+    /// at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if no message has been
+    /// received in time.
+    ///
+    /// [`recv_blocking`]: #method.recv_blocking
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<D, ReceiveError> {
+        blocking::recv_until(self, Instant::now() + timeout)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `timeout` has elapsed (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_within<T: Timer>(
+        &mut self,
+        timeout: Duration,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        let deadline = timer.now() + timeout;
+        self.recv_deadline(deadline, timer)
+    }
+
+    /// Returns a future that receives a message over the
+    /// channel (see [`recv`]), or resolves to
+    /// `Err(ReceiveError::Timeout)` if it couldn't before
+    /// `deadline` (as measured by `timer`).
+    ///
+    /// [`recv`]: #method.recv
+    pub fn recv_deadline<T: Timer>(
+        &mut self,
+        deadline: Instant,
+        timer: T,
+    ) -> RecvTimeoutFuture<'_, D, T> {
+        RecvTimeoutFuture::new(self, deadline, timer)
+    }
+
+    /// Tries to receive a message over the channel,
+    /// registering the task to be woken up when one is
+    /// sent if there is none available.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<D, ReceiveError>> {
+        self.receiver.poll_recv(cx)
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (closing it and dropping the messages still in its
+    /// buffer, since it is its only receiver), returning
+    /// `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        self.receiver.disconnect()
     }
 
     /// Tries to close the channel, returning `Ok(())` if it
-    /// succeeded, or `Err(CloseError::Closed)` if the channel
-    /// was already closed.
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
     pub fn close(&mut self) -> Result<(), CloseError> {
-        if self.closed {
-            Err(CloseError::Closed)
-        } else if let Some(ref mut receiver) = self.receiver {
-            receiver.close();
-            self.closed = true;
-            Ok(())
-        } else {
-            unreachable!();
-        }
+        self.receiver.close()
     }
 }
 
+impl<D> Unpin for Sender<D> {}
 impl<D> Unpin for Receiver<D> {}
 
 impl<D> Sink<D> for Sender<D> {
-    // FIXME: -`()` +`D` (the issue being that `poll_ready`,
-    //   `poll_flush` and `poll_close` can't return `D` since
-    //   they don't get any data in the first place).
-    type SinkError = SendError<()>;
-
-    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).poll_ready(cx).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    // NOTE: the buffer is never full, so the message is
+    //   sent (or returned in an error) by `start_send`.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
     }
 
-    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<()>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).start_send(msg).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        self.get_mut().sender.start_send(msg)
     }
 
-    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).poll_flush(cx).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_pending(cx)
     }
 
-    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<()>>> {
-        let receiver = self.get_mut();
-        Pin::new(&mut receiver.sender).poll_close(cx).map_err(|_| {
-            if receiver.disconnected {
-                SendError::Disconnected(())
-            } else if receiver.closed {
-                SendError::Closed(())
-            } else {
-                receiver.closed = true;
-                SendError::Closed(())
-            }
-        })
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        self.get_mut().sender.poll_close(cx)
     }
 }
 
 impl<D> FusedStream for Receiver<D> {
     fn is_terminated(&self) -> bool {
-        self.closed
+        self.receiver.is_terminated()
     }
 }
 
 impl<D> Stream for Receiver<D> {
     type Item = D;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
-        if let Some(ref mut receiver) = self.receiver {
-            match Pin::new(receiver).poll_next(cx) {
-                Poll::Ready(None) => {
-                    self.receiver = None;
-                    self.closed = true;
-                    Poll::Ready(None)
-                }
-                poll => poll,
-            }
-        } else if self.closed {
-            Poll::Ready(None)
-        } else {
-            unreachable!();
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        match self.get_mut().poll_recv(cx) {
+            Poll::Ready(Ok(data)) => Poll::Ready(Some(data)),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
         }
     }
 }
 
 impl<D> Clone for Sender<D> {
+    /// # Panics
+    ///
+    /// This method panics if the channel already has the
+    /// maximum number of senders that it can have (see
+    /// [`try_clone`]).
+    ///
+    /// [`try_clone`]: #method.try_clone
     fn clone(&self) -> Sender<D> {
-        Sender {
-            sender: self.sender.clone(),
-            ..*self
-        }
+        self.try_clone()
+            .expect("the channel already has the maximum number of senders")
     }
 }
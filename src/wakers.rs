@@ -0,0 +1,63 @@
+use std::mem;
+use std::sync::atomic;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::task::Waker;
+
+/// A set of wakers of tasks that are waiting for
+/// a channel's state to change.
+pub(crate) struct Wakers {
+    /// The number of registered wakers, used to avoid
+    /// locking `wakers` when there's nothing to wake.
+    len: AtomicUsize,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Wakers {
+    pub(crate) fn new() -> Wakers {
+        Wakers {
+            len: AtomicUsize::new(0),
+            wakers: Mutex::new(vec![]),
+        }
+    }
+
+    /// Registers `waker` to be woken up the next time
+    /// [`wake_all`] is called, if it isn't already.
+    ///
+    /// Callers must check the state they are waiting
+    /// on after calling this method.
+    ///
+    /// [`wake_all`]: #method.wake_all
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+            self.len.store(wakers.len(), Ordering::SeqCst);
+        }
+        drop(wakers);
+
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Wakes up and unregisters all the registered
+    /// wakers.
+    ///
+    /// Callers must update the state that the wakers
+    /// are waiting on before calling this method.
+    pub(crate) fn wake_all(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.len.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let mut wakers = self.wakers.lock().unwrap();
+        let woken = mem::take(&mut *wakers);
+        self.len.store(0, Ordering::SeqCst);
+        drop(wakers);
+
+        for waker in woken {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_core::future::Future;
+use futures_core::stream::FusedStream;
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+
+use crate::blocking;
+use crate::error::*;
+use crate::wakers::Wakers;
+
+/// Creates a new watch channel, which only holds the
+/// latest value that has been sent over it, starting
+/// with `init`.
+pub fn new<D>(init: D) -> (Sender<D>, Receiver<D>) {
+    let inner = Arc::new(Inner::new(init));
+
+    (Sender::new(inner.clone()), Receiver::new(inner))
+}
+
+#[derive(Debug)]
+/// The sending half of a watch channel, which shares the
+/// channel's state with the other senders and the
+/// receivers.
+pub struct Sender<D> {
+    /// Whether the sender has disconnected itself from the
+    /// channel.
+    disconnected: bool,
+    inner: Arc<Inner<D>>,
+}
+
+#[derive(Debug)]
+/// A receiving half of a watch channel, which shares the
+/// channel's state with the senders and the other
+/// receivers.
+///
+/// The receiver only ever sees the latest value that has
+/// been sent over the channel, skipping the ones that have
+/// been overwritten before it could see them.
+pub struct Receiver<D> {
+    /// The version of the last value that the receiver
+    /// has seen.
+    version: usize,
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    disconnected: bool,
+    inner: Arc<Inner<D>>,
+}
+
+#[derive(Debug)]
+/// A reference to the latest value that has been sent
+/// over a watch channel (see [`Receiver::borrow`]).
+///
+/// The senders can't update the value while it is
+/// borrowed.
+///
+/// [`Receiver::borrow`]: struct.Receiver.html#method.borrow
+pub struct Ref<'r, D> {
+    value: RwLockReadGuard<'r, D>,
+}
+
+#[derive(Debug)]
+/// A future that waits for the value of a watch channel
+/// to change (see [`Receiver::changed`]).
+///
+/// [`Receiver::changed`]: struct.Receiver.html#method.changed
+pub struct ChangedFuture<'r, D> {
+    receiver: &'r mut Receiver<D>,
+}
+
+/// The state shared by the senders and the receivers of
+/// a watch channel.
+struct Inner<D> {
+    value: RwLock<D>,
+    /// The version of `value`, which is incremented every
+    /// time a new value is sent.
+    version: AtomicUsize,
+    /// Whether the channel has been closed.
+    closed: AtomicBool,
+    /// The number of connected senders.
+    senders: AtomicUsize,
+    /// The number of connected receivers.
+    receivers: AtomicUsize,
+    /// The wakers of the receivers' tasks that are
+    /// waiting for the value to change.
+    wakers: Wakers,
+}
+
+impl<D> Sender<D> {
+    fn new(inner: Arc<Inner<D>>) -> Sender<D> {
+        Sender {
+            disconnected: false,
+            inner,
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the senders or receivers).
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Whether the sender has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Replaces the channel's value with `data`, returning
+    /// `Ok(())` if it has been successfully sent, or either
+    /// `Err(SendError::Disconnected)` if the  sender has
+    /// disconnected itself from the channel or
+    /// `Err(SendError::Closed)` if the channel has been closed.
+    pub fn send(&mut self, data: D) -> Result<(), SendError<D>> {
+        if self.disconnected {
+            return Err(SendError::Disconnected(data));
+        } else if self.inner.is_closed() {
+            return Err(SendError::Closed(data));
+        }
+
+        let mut value = self.inner.value.write().unwrap();
+        let old = mem::replace(&mut *value, data);
+        self.inner.version.fetch_add(1, Ordering::SeqCst);
+        drop(value);
+
+        self.inner.wakers.wake_all();
+        drop(old);
+
+        Ok(())
+    }
+
+    /// Tries to disconnect the sender from the channel,
+    /// returning `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the sender
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.inner.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.inner.remove_sender();
+            self.disconnected = true;
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the sender already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<D> Receiver<D> {
+    fn new(inner: Arc<Inner<D>>) -> Receiver<D> {
+        Receiver {
+            version: 0,
+            disconnected: false,
+            inner,
+        }
+    }
+
+    /// Whether the channel has been closed (by any of
+    /// the receivers or senders).
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Whether the receiver has disconnected itself from
+    /// the channel.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Whether a new value has been sent over the channel
+    /// since the receiver last saw it (see [`changed`]).
+    ///
+    /// [`changed`]: #method.changed
+    pub fn has_changed(&self) -> bool {
+        !self.disconnected && self.inner.version.load(Ordering::SeqCst) != self.version
+    }
+
+    /// Returns a reference to the latest value that has
+    /// been sent over the channel, without marking it as
+    /// seen.
+    pub fn borrow(&self) -> Ref<'_, D> {
+        Ref {
+            value: self.inner.value.read().unwrap(),
+        }
+    }
+
+    /// Returns a future that waits for a new value to be
+    /// sent over the channel, marking it as seen and
+    /// resolving to `Ok(())` once one has been, or either
+    /// to `Err(ReceiveError::Disconnected)` if the receiver
+    /// has disconnected itself from the channel or to
+    /// `Err(ReceiveError::Closed)` if the channel has been
+    /// closed and the receiver has already seen its latest
+    /// value.
+    pub fn changed(&mut self) -> ChangedFuture<'_, D> {
+        ChangedFuture { receiver: self }
+    }
+
+    /// Waits for a new value to be sent over the channel
+    /// (see [`changed`]), blocking the current thread until
+    /// one has been.
+    ///
+    /// [`changed`]: #method.changed
+    pub fn changed_blocking(&mut self) -> Result<(), ReceiveError> {
+        blocking::wait(|cx| self.poll_changed(cx))
+    }
+
+    /// Waits for a new value to be sent over the channel
+    /// (see [`changed_blocking`]), blocking the current
+    /// thread for at most `timeout`, and returning
+    /// `Err(ReceiveError::Timeout)` if none has been sent
+    /// in time.
+    ///
+    /// [`changed_blocking`]: #method.changed_blocking
+    pub fn changed_timeout(&mut self, timeout: Duration) -> Result<(), ReceiveError> {
+        blocking::wait_until(Instant::now() + timeout, |cx| self.poll_changed(cx))
+            .unwrap_or(Err(ReceiveError::Timeout))
+    }
+
+    /// Checks whether a new value has been sent over the
+    /// channel, registering the task to be woken up when
+    /// one is if it hasn't.
+    pub(crate) fn poll_changed(&mut self, cx: &mut Context) -> Poll<Result<(), ReceiveError>> {
+        if self.disconnected {
+            return Poll::Ready(Err(ReceiveError::Disconnected));
+        }
+
+        match self.try_changed() {
+            Err(ReceiveError::Empty) => (),
+            res => return Poll::Ready(res),
+        }
+
+        self.inner.wakers.register(cx.waker());
+
+        match self.try_changed() {
+            Err(ReceiveError::Empty) => Poll::Pending,
+            res => Poll::Ready(res),
+        }
+    }
+
+    /// Tries to disconnect the receiver from the channel
+    /// (closing it if it was the last receiver), returning
+    /// `Ok(())` if it succeeded, or either
+    /// `Err(DisconnectError::Disconnected)` if the receiver
+    /// already disconnected itself, or
+    /// `Err(DisconnectError::Closed)` if the channel was
+    /// already closed.
+    pub fn disconnect(&mut self) -> Result<(), DisconnectError> {
+        if self.disconnected {
+            Err(DisconnectError::Disconnected)
+        } else if self.inner.is_closed() {
+            Err(DisconnectError::Closed)
+        } else {
+            self.inner.remove_receiver();
+            self.disconnected = true;
+            Ok(())
+        }
+    }
+
+    /// Tries to close the channel, returning `Ok(())` if it
+    /// succeeded, or either `Err(CloseError::Disconnected)`
+    /// if the receiver already disconnected itself, or
+    /// `Err(CloseError::Closed)` if the channel was already
+    /// closed.
+    pub fn close(&mut self) -> Result<(), CloseError> {
+        if self.disconnected {
+            Err(CloseError::Disconnected)
+        } else if !self.inner.close() {
+            Err(CloseError::Closed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Marks the latest value as seen if the receiver
+    /// hasn't seen it yet, or returns an error.
+    fn try_changed(&mut self) -> Result<(), ReceiveError> {
+        let closed = self.inner.is_closed();
+
+        let version = self.inner.version.load(Ordering::SeqCst);
+        if version != self.version {
+            self.version = version;
+            Ok(())
+        } else if closed {
+            Err(ReceiveError::Closed)
+        } else {
+            Err(ReceiveError::Empty)
+        }
+    }
+}
+
+impl<D> Inner<D> {
+    fn new(init: D) -> Inner<D> {
+        Inner {
+            value: RwLock::new(init),
+            version: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            senders: AtomicUsize::new(1),
+            receivers: AtomicUsize::new(1),
+            wakers: Wakers::new(),
+        }
+    }
+
+    /// Whether the channel has been closed.
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Closes the channel, returning whether it wasn't
+    /// already closed.
+    fn close(&self) -> bool {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        self.wakers.wake_all();
+        true
+    }
+
+    /// Unregisters a sender, closing the channel if it
+    /// was the last one.
+    fn remove_sender(&self) {
+        if self.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.close();
+        }
+    }
+
+    /// Unregisters a receiver, closing the channel if it
+    /// was the last one.
+    fn remove_receiver(&self) {
+        if self.receivers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.close();
+        }
+    }
+}
+
+impl<D> Deref for Ref<'_, D> {
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        &self.value
+    }
+}
+
+impl<D> Unpin for Receiver<D> {}
+
+impl<D> Future for ChangedFuture<'_, D> {
+    type Output = Result<(), ReceiveError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), ReceiveError>> {
+        self.get_mut().receiver.poll_changed(cx)
+    }
+}
+
+impl<D> Sink<D> for Sender<D> {
+    // NOTE: the value is sent (or returned in an error)
+    //   by `start_send`, so `poll_ready` and `poll_flush`
+    //   never have to fail.
+    type SinkError = SendError<D>;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: D) -> Result<(), SendError<D>> {
+        self.get_mut().send(msg)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), SendError<D>>> {
+        let sender = self.get_mut();
+        if !sender.disconnected {
+            sender.inner.remove_sender();
+            sender.disconnected = true;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<D: Clone> FusedStream for Receiver<D> {
+    fn is_terminated(&self) -> bool {
+        self.disconnected || (self.inner.is_closed() && !self.has_changed())
+    }
+}
+
+impl<D: Clone> Stream for Receiver<D> {
+    // NOTE: the stream yields a clone of the value every
+    //   time it changes.
+    type Item = D;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        let receiver = self.get_mut();
+        match receiver.poll_changed(cx) {
+            Poll::Ready(Ok(())) => {
+                let value = receiver.inner.value.read().unwrap();
+                receiver.version = receiver.inner.version.load(Ordering::SeqCst);
+
+                Poll::Ready(Some(value.clone()))
+            }
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<D> Clone for Sender<D> {
+    fn clone(&self) -> Sender<D> {
+        if !self.disconnected {
+            self.inner.senders.fetch_add(1, Ordering::SeqCst);
+        }
+
+        Sender {
+            disconnected: self.disconnected,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<D> Clone for Receiver<D> {
+    fn clone(&self) -> Receiver<D> {
+        if !self.disconnected {
+            self.inner.receivers.fetch_add(1, Ordering::SeqCst);
+        }
+
+        Receiver {
+            version: self.version,
+            disconnected: self.disconnected,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<D> Drop for Sender<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_sender();
+        }
+    }
+}
+
+impl<D> Drop for Receiver<D> {
+    fn drop(&mut self) {
+        if !self.disconnected {
+            self.inner.remove_receiver();
+        }
+    }
+}
+
+impl<D> fmt::Debug for Inner<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Inner")
+            .field("version", &self.version.load(Ordering::SeqCst))
+            .field("closed", &self.is_closed())
+            .field("senders", &self.senders.load(Ordering::SeqCst))
+            .field("receivers", &self.receivers.load(Ordering::SeqCst))
+            .finish()
+    }
+}
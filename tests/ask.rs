@@ -0,0 +1,200 @@
+#![feature(async_await)]
+
+use std::task::Poll;
+use std::time::Duration;
+
+use aktoro_channel::ask::*;
+use aktoro_channel::timer::*;
+use aktoro_channel::*;
+use futures_util::poll;
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = bounded::new::<Envelope<u8, u16>>(8);
+
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.request, 42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(envelope.reply(420), Ok(()));
+        assert_eq!(envelope.reply(240), Err(SendError::Full(240)));
+
+        assert_eq!(fut.await, Ok(420));
+    }
+
+    // FULL CHANNEL
+    let (mut send, mut recv) = bounded::new::<Envelope<u8, u16>>(1);
+
+    let (envelope, _reply) = Envelope::new(16);
+    assert!(send.try_send(envelope).is_ok());
+
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(recv.try_recv().unwrap().request, 16);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.request, 42);
+        assert_eq!(envelope.reply(420), Ok(()));
+
+        assert_eq!(fut.await, Ok(420));
+    }
+
+    // DROPPED REPLY
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        drop(recv.try_recv().unwrap());
+
+        let err = fut.await.unwrap_err();
+        assert!(err.is_dropped());
+        assert_eq!(err, AskError::Dropped(ReceiveError::SenderDropped));
+    }
+
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.reply.close(), Ok(()));
+
+        assert_eq!(fut.await, Err(AskError::Dropped(ReceiveError::Closed)));
+    }
+
+    // SEND FAILURE
+    assert_eq!(recv.close(), Ok(()));
+
+    let err = send.ask(42).await.unwrap_err();
+    assert!(err.is_send());
+    assert_eq!(err, AskError::Send(SendError::Closed(42)));
+
+    // TIMEOUT
+    let timer = ManualTimer::new();
+    let (mut send, mut recv) = bounded::new::<Envelope<u8, u16>>(8);
+
+    {
+        let mut fut = send.ask_timeout(42, Duration::from_secs(1), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let _envelope = recv.try_recv().unwrap();
+
+        timer.advance(Duration::from_millis(500));
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        timer.advance(Duration::from_millis(500));
+
+        let err = fut.await.unwrap_err();
+        assert!(err.is_timeout());
+        assert_eq!(err, AskError::Timeout);
+    }
+
+    {
+        let deadline = timer.now() + Duration::from_secs(1);
+        let mut fut = send.ask_deadline(42, deadline, &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.reply(420), Ok(()));
+
+        assert_eq!(fut.await, Ok(420));
+    }
+
+    // UNBOUNDED
+    let (mut send, mut recv) = unbounded::new::<Envelope<u8, u16>>();
+
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.request, 42);
+        assert_eq!(envelope.reply(420), Ok(()));
+
+        assert_eq!(fut.await, Ok(420));
+    }
+
+    {
+        let mut fut = send.ask_timeout(42, Duration::from_secs(1), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let _envelope = recv.try_recv().unwrap();
+
+        timer.advance(Duration::from_secs(1));
+        assert_eq!(fut.await, Err(AskError::Timeout));
+    }
+
+    assert_eq!(recv.close(), Ok(()));
+    assert_eq!(
+        send.ask(42).await,
+        Err(AskError::Send(SendError::Closed(42)))
+    );
+
+    // MPMC
+    let (mut send, mut recv) = mpmc::bounded::<Envelope<u8, u16>>(8);
+
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.reply(420), Ok(()));
+
+        assert_eq!(fut.await, Ok(420));
+    }
+
+    // PRIORITY
+    let (mut send, mut recv) = priority::new::<Envelope<u8, u16>>(&[8, 8]);
+
+    let mut system = send.bind(0);
+
+    {
+        let mut user = send.ask(42);
+        assert_eq!(poll!(&mut user), Poll::Pending);
+
+        let mut fut = system.ask(24);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.request, 24);
+        assert_eq!(envelope.reply(240), Ok(()));
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.request, 42);
+        assert_eq!(envelope.reply(420), Ok(()));
+
+        assert_eq!(fut.await, Ok(240));
+        assert_eq!(user.await, Ok(420));
+    }
+
+    {
+        let mut fut = send.ask_to(0, 16);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.request, 16);
+        assert_eq!(envelope.reply(160), Ok(()));
+
+        assert_eq!(fut.await, Ok(160));
+    }
+
+    // CHANNEL
+    let (mut send, mut recv) = channel::unbounded::<Envelope<u8, u16>>();
+
+    {
+        let mut fut = send.ask(42);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        let mut envelope = recv.try_recv().unwrap();
+        assert_eq!(envelope.reply(420), Ok(()));
+
+        assert_eq!(fut.await, Ok(420));
+    }
+}
@@ -1,15 +1,28 @@
 #![feature(async_await)]
 
+#[macro_use]
+mod common;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 
+use aktoro_channel::bounded::Overflow;
 use aktoro_channel::*;
 use futures_util::poll;
+use futures_util::task::noop_waker_ref;
 use futures_util::SinkExt;
 use futures_util::StreamExt;
 
 type Sender = bounded::Sender<u8>;
 type Receiver = bounded::Receiver<u8>;
 
+channel_helpers!(try_send);
+
 #[runtime::test]
 async fn test() {
     // NORMAL
@@ -26,9 +39,7 @@ async fn test() {
     recv_empty(&mut recv);
 
     // FULL
-    let (mut send, mut recv) = bounded::new::<u8>(1);
-    // NOTE: in `future_channel`, bounded channels have a capacity
-    //   of `buf` (in this case 1), plus the number of senders
+    let (mut send, mut recv) = bounded::new::<u8>(2);
 
     send_is_default(&send);
     recv_is_default(&mut recv);
@@ -86,8 +97,8 @@ async fn test() {
         send_ok(24, &mut send);
 
         assert_eq!(send.disconnect(), Ok(()));
-        assert!(!send.closed);
-        assert!(send.disconnected);
+        assert!(!send.is_closed());
+        assert!(send.is_disconnected());
         assert_eq!(send.disconnect(), Err(DisconnectError::Disconnected));
 
         assert_eq!(send.close(), Err(CloseError::Disconnected));
@@ -96,7 +107,7 @@ async fn test() {
 
         assert_eq!(
             SinkExt::send(&mut send, 32).await,
-            Err(SendError::Disconnected(()))
+            Err(SendError::Disconnected(32))
         );
     }
 
@@ -119,8 +130,9 @@ async fn test() {
     send_ok(42, &mut send);
 
     assert_eq!(send.disconnect(), Ok(()));
-    assert!(!send.closed);
-    assert!(send.disconnected);
+    assert!(send.is_closed());
+    assert!(send.is_disconnected());
+    assert!(recv.is_closed());
     assert_eq!(send.disconnect(), Err(DisconnectError::Disconnected));
 
     assert_eq!(send.close(), Err(CloseError::Disconnected));
@@ -132,7 +144,7 @@ async fn test() {
 
     assert_eq!(
         SinkExt::send(&mut send, 32).await,
-        Err(SendError::Disconnected(()))
+        Err(SendError::Disconnected(32))
     );
 
     // CLOSING SEND
@@ -147,8 +159,8 @@ async fn test() {
         let mut send = send.clone();
 
         assert_eq!(send.close(), Ok(()));
-        assert!(send.closed);
-        assert!(!send.disconnected);
+        assert!(send.is_closed());
+        assert!(!send.is_disconnected());
         assert_eq!(send.close(), Err(CloseError::Closed));
 
         assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
@@ -156,14 +168,16 @@ async fn test() {
         send_closed(24, &mut send);
     }
 
+    assert!(send.is_closed());
+    assert!(recv.is_closed());
+
     assert_eq!(send.close(), Err(CloseError::Closed));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
 
     assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
-    // FIXME: assert_eq!(recv.close(), Err(CloseError::Closed));
-    assert_eq!(recv.close(), Ok(()));
-    assert!(recv.closed);
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+    assert!(recv.is_closed());
 
     recv_ok(42, &mut recv);
 
@@ -172,7 +186,7 @@ async fn test() {
 
     assert_eq!(
         SinkExt::send(&mut send, 32).await,
-        Err(SendError::Closed(()))
+        Err(SendError::Closed(32))
     );
 
     // CLOSING RECV
@@ -184,13 +198,14 @@ async fn test() {
     send_ok(42, &mut send);
 
     assert_eq!(recv.close(), Ok(()));
-    assert!(recv.closed);
+    assert!(recv.is_closed());
+    assert!(send.is_closed());
     assert_eq!(recv.close(), Err(CloseError::Closed));
 
     assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
     assert_eq!(send.close(), Err(CloseError::Closed));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
 
     recv_ok(42, &mut recv);
 
@@ -211,10 +226,10 @@ async fn test() {
 
     recv_ok(42, &mut recv);
     recv_closed(&mut recv);
-    assert!(recv.closed);
+    assert!(recv.is_closed());
 
     assert_eq!(recv.close(), Err(CloseError::Closed));
-    assert!(recv.closed);
+    assert!(recv.is_closed());
 
     // DROPING RECV
     let (mut send, mut recv) = bounded::new::<u8>(8);
@@ -227,58 +242,466 @@ async fn test() {
     drop(recv);
 
     send_closed(24, &mut send);
-    assert!(send.closed);
+    assert!(send.is_closed());
 
     assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
     assert_eq!(send.close(), Err(CloseError::Closed));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
 }
 
-fn send_is_default(send: &Sender) {
-    assert!(!send.closed);
-    assert!(!send.disconnected);
+#[test]
+fn exact_capacity() {
+    let (mut send, mut recv) = bounded::new::<u8>(1);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    {
+        let mut send = send.clone();
+
+        send_ok(42, &mut send);
+        send_full(24, &mut send);
+    }
+
+    send_full(16, &mut send);
+
+    recv_ok(42, &mut recv);
+
+    send_ok(8, &mut send);
+    send_full(4, &mut send);
+
+    recv_ok(8, &mut recv);
+    recv_empty(&mut recv);
 }
 
-fn recv_is_default(recv: &mut Receiver) {
-    assert!(!recv.closed);
-    recv_empty(recv);
+#[runtime::test]
+async fn send_future() {
+    let (mut send, mut recv) = bounded::new::<u8>(1);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    {
+        let mut fut = send.send(24);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        recv_ok(42, &mut recv);
+
+        assert_eq!(fut.await, Ok(()));
+    }
+
+    recv_ok(24, &mut recv);
+    assert_eq!(send.send(16).await, Ok(()));
+
+    {
+        let mut fut = send.send(8);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(recv.close(), Ok(()));
+
+        assert_eq!(fut.await, Err(SendError::Closed(8)));
+    }
+
+    recv_ok(16, &mut recv);
+    recv_closed(&mut recv);
 }
 
-fn send_ok(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Ok(()));
-    assert!(!send.closed);
-    assert!(!send.disconnected);
+#[runtime::test]
+async fn recv_future() {
+    let (mut send, mut recv) = bounded::new::<u8>(8);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    {
+        let mut fut = recv.recv();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        send_ok(42, &mut send);
+
+        assert_eq!(fut.await, Ok(42));
+    }
+
+    send_ok(24, &mut send);
+    assert_eq!(send.close(), Ok(()));
+
+    assert_eq!(recv.recv().await, Ok(24));
+    assert_eq!(recv.recv().await, Err(ReceiveError::Closed));
 }
 
-fn send_full(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Err(SendError::Full(data)));
-    assert!(!send.closed);
-    assert!(!send.disconnected);
+#[runtime::test]
+async fn sink_recovery() {
+    let (mut send, mut recv) = bounded::new::<u8>(1);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(poll!(SinkExt::send(&mut send, 24)), Poll::Pending);
+    assert_eq!(poll!(SinkExt::flush(&mut send)), Poll::Pending);
+
+    recv_ok(42, &mut recv);
+
+    assert_eq!(SinkExt::flush(&mut send).await, Ok(()));
+    recv_ok(24, &mut recv);
+
+    send_ok(16, &mut send);
+
+    assert_eq!(poll!(SinkExt::send(&mut send, 8)), Poll::Pending);
+    assert_eq!(recv.close(), Ok(()));
+
+    assert_eq!(SinkExt::flush(&mut send).await, Err(SendError::Closed(8)));
+    assert_eq!(SinkExt::flush(&mut send).await, Ok(()));
 }
 
-fn send_disconnected(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Err(SendError::Disconnected(data)));
-    assert!(!send.closed);
-    assert!(send.disconnected);
+#[runtime::test]
+async fn disconnecting_recv() {
+    let (mut send, mut recv) = bounded::new::<u8>(8);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv.disconnect(), Ok(()));
+    assert!(recv.is_closed());
+    assert!(recv.is_disconnected());
+    assert_eq!(recv.disconnect(), Err(DisconnectError::Disconnected));
+
+    assert_eq!(recv.close(), Err(CloseError::Disconnected));
+
+    recv_disconnected(&mut recv);
+    assert_eq!(recv.recv().await, Err(ReceiveError::Disconnected));
+    assert_eq!(recv.next().await, None);
+
+    send_closed(24, &mut send);
 }
 
-fn send_closed(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Err(SendError::Closed(data)));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+#[runtime::test]
+async fn receipts() {
+    let (mut send, mut recv) = bounded::new::<u8>(1);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    let mut receipt = send.try_send_notify(42).unwrap();
+    assert_eq!(poll!(&mut receipt), Poll::Pending);
+
+    assert_eq!(send.try_send_notify(24).unwrap_err(), SendError::Full(24));
+
+    recv_ok(42, &mut recv);
+    assert_eq!(receipt.await, Ok(()));
+
+    let receipt = send.try_send_notify(24).unwrap();
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(receipt.await, Ok(()));
+
+    let receipt = send.try_send_notify(16).unwrap();
+    assert_eq!(recv.close(), Ok(()));
+    recv_ok(16, &mut recv);
+    assert_eq!(receipt.await, Ok(()));
+
+    let (mut send, recv) = bounded::new::<u8>(1);
+
+    let receipt = send.try_send_notify(42).unwrap();
+    drop(recv);
+    assert_eq!(receipt.await, Err(ReceiveError::Closed));
+
+    assert_eq!(send.try_send_notify(24).unwrap_err(), SendError::Closed(24));
 }
 
-fn recv_ok(data: u8, recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Ok(data));
+#[test]
+fn limits() {
+    let (send, mut recv) = bounded::with_max_senders::<u8>(8, 2);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    {
+        let mut send = send.try_clone().unwrap();
+        assert_eq!(send.try_clone().unwrap_err(), CloneError::Limit);
+
+        send_ok(42, &mut send);
+    }
+
+    let mut send2 = send.try_clone().unwrap();
+    send_ok(24, &mut send2);
+
+    assert_eq!(send2.disconnect(), Ok(()));
+    assert!(send2.try_clone().unwrap().is_disconnected());
+
+    let mut send2 = send.clone();
+    send_ok(16, &mut send2);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_ok(16, &mut recv);
+    recv_empty(&mut recv);
 }
 
-fn recv_empty(recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
-    assert!(!recv.closed);
+#[runtime::test]
+async fn rendezvous() {
+    let (mut send, mut recv) = bounded::new::<u8>(0);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_full(42, &mut send);
+
+    {
+        let mut send_fut = send.send(42);
+        assert_eq!(poll!(&mut send_fut), Poll::Pending);
+
+        let mut recv_fut = recv.recv();
+        assert_eq!(poll!(&mut recv_fut), Poll::Pending);
+
+        // NOTE: the message is handed off, but the receiver
+        //   hasn't taken it yet.
+        assert_eq!(poll!(&mut send_fut), Poll::Pending);
+
+        assert_eq!(recv_fut.await, Ok(42));
+        assert_eq!(send_fut.await, Ok(()));
+    }
+
+    {
+        let mut sink_fut = SinkExt::send(&mut send, 42);
+        assert_eq!(poll!(&mut sink_fut), Poll::Pending);
+
+        let mut recv_fut = recv.recv();
+        assert_eq!(poll!(&mut recv_fut), Poll::Pending);
+
+        // NOTE: the message is handed off when the sink is
+        //   flushed, but the receiver hasn't taken it yet.
+        assert_eq!(poll!(&mut sink_fut), Poll::Pending);
+        assert_eq!(poll!(&mut sink_fut), Poll::Pending);
+
+        assert_eq!(recv_fut.await, Ok(42));
+        assert_eq!(sink_fut.await, Ok(()));
+    }
+
+    recv_empty(&mut recv);
+    send_full(24, &mut send);
+
+    {
+        let mut recv_fut = recv.recv();
+        assert_eq!(poll!(&mut recv_fut), Poll::Pending);
+
+        send_ok(24, &mut send);
+        send_full(16, &mut send);
+
+        assert_eq!(recv_fut.await, Ok(24));
+    }
+
+    send_full(16, &mut send);
+
+    // NOTE: the receiver stops waiting when its recv future
+    //   is dropped.
+    assert_eq!(poll!(recv.recv()), Poll::Pending);
+    send_full(16, &mut send);
+
+    {
+        let timer = timer::ManualTimer::new();
+        let mut recv_fut = recv.recv_within(Duration::from_secs(1), &timer);
+        assert_eq!(poll!(&mut recv_fut), Poll::Pending);
+
+        timer.advance(Duration::from_secs(1));
+        assert_eq!(recv_fut.await, Err(ReceiveError::Timeout));
+    }
+
+    send_full(16, &mut send);
+
+    {
+        let (_send2, mut recv2) = unbounded::new::<u8>();
+        let mut select = select::Select::new(select::Fairness::Biased)
+            .recv(&mut recv, |data| data)
+            .recv(&mut recv2, |data| data);
+
+        // NOTE: a select isn't `Send`, so it is polled without
+        //   being held across an await.
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert_eq!(Pin::new(&mut select.select()).poll(&mut cx), Poll::Pending);
+    }
+
+    send_full(16, &mut send);
+
+    assert_eq!(poll!(recv.next()), Poll::Pending);
+    drop(send);
+
+    assert_eq!(recv.next().await, None);
+}
+
+#[runtime::test]
+async fn overflow() {
+    // OVERFLOW REJECT
+    let (mut send, mut recv) = bounded::with_overflow::<u8>(1, Overflow::Reject);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(send.overflow(), Overflow::Reject);
+
+    send_ok(42, &mut send);
+    send_full(24, &mut send);
+
+    assert_eq!(send.send(24).await, Err(SendError::Full(24)));
+    assert_eq!(SinkExt::send(&mut send, 24).await, Err(SendError::Full(24)));
+
+    recv_ok(42, &mut recv);
+    recv_empty(&mut recv);
+    assert_eq!(send.evicted(), 0);
+
+    // OVERFLOW DROP OLDEST
+    let (mut send, mut recv) = bounded::with_overflow::<u8>(2, Overflow::DropOldest);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    send_ok(16, &mut send);
+
+    assert_eq!(send.try_send_evicting(8), Ok(Some(24)));
+    assert_eq!(send.send(4).await, Ok(()));
+    assert_eq!(recv.evicted(), 3);
+
+    let receipt = send.try_send_notify(2).unwrap();
+    send_ok(1, &mut send);
+    send_ok(0, &mut send);
+    assert_eq!(receipt.await, Err(ReceiveError::Closed));
+
+    recv_ok(1, &mut recv);
+    recv_ok(0, &mut recv);
+    recv_empty(&mut recv);
+
+    assert_eq!(send.try_send_evicting(42), Ok(None));
+    recv_ok(42, &mut recv);
+
+    // OVERFLOW DROP NEWEST
+    let (mut send, mut recv) = bounded::with_overflow::<u8>(2, Overflow::DropNewest);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    send_ok(16, &mut send);
+
+    assert_eq!(send.try_send_evicting(8), Ok(Some(8)));
+    assert_eq!(SinkExt::send(&mut send, 4).await, Ok(()));
+    assert_eq!(send.evicted(), 3);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    // EVICT CALLBACK
+    let evicted = Arc::new(Mutex::new(vec![]));
+    let (mut send, mut recv) = {
+        let evicted = evicted.clone();
+        bounded::with_evict_callback::<u8, _>(1, Overflow::DropOldest, move |data| {
+            evicted.lock().unwrap().push(data)
+        })
+    };
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    assert_eq!(send.try_send_evicting(16), Ok(Some(24)));
+    send_ok(8, &mut send);
+
+    recv_ok(8, &mut recv);
+    assert_eq!(*evicted.lock().unwrap(), vec![42, 16]);
+    assert_eq!(recv.evicted(), 3);
+}
+
+#[test]
+fn blocking() {
+    let (mut send, mut recv) = bounded::new::<u32>(1);
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..1000 {
+            assert_eq!(send.send_blocking(i), Ok(()));
+        }
+    });
+
+    for i in 0..1000 {
+        assert_eq!(recv.recv_blocking(), Ok(i));
+    }
+
+    thread.join().unwrap();
+    assert_eq!(recv.recv_blocking(), Err(ReceiveError::Closed));
+
+    // SEND TIMEOUT
+    let (mut send, mut recv) = bounded::new::<u32>(1);
+
+    assert_eq!(send.send_timeout(42, Duration::from_millis(10)), Ok(()));
+    assert_eq!(
+        send.send_timeout(24, Duration::from_millis(10)),
+        Err(SendError::Timeout(24))
+    );
+
+    assert_eq!(recv.recv_blocking(), Ok(42));
+
+    // RENDEZVOUS
+    let (mut send, mut recv) = bounded::new::<u32>(0);
+
+    assert_eq!(
+        recv.recv_timeout(Duration::from_millis(10)),
+        Err(ReceiveError::Timeout)
+    );
+    assert_eq!(
+        send.send_timeout(42, Duration::from_millis(10)),
+        Err(SendError::Timeout(42))
+    );
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..100 {
+            assert_eq!(send.send_blocking(i), Ok(()));
+        }
+    });
+
+    for i in 0..100 {
+        assert_eq!(recv.recv_timeout(Duration::from_secs(10)), Ok(i));
+    }
+
+    thread.join().unwrap();
+    assert_eq!(
+        recv.recv_timeout(Duration::from_secs(10)),
+        Err(ReceiveError::Closed)
+    );
 }
 
-fn recv_closed(recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Err(ReceiveError::Closed));
-    assert!(recv.closed);
+#[test]
+fn drop_oldest() {
+    let (send, mut recv) = bounded::with_overflow::<u32>(4, Overflow::DropOldest);
+
+    let threads = (0..4)
+        .map(|_| {
+            let mut send = send.clone();
+            std::thread::spawn(move || {
+                for i in 0..1000 {
+                    assert_eq!(send.try_send(i), Ok(()));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut received = 0;
+    while received + send.evicted() < 4000 {
+        if recv.try_recv().is_ok() {
+            received += 1;
+        }
+    }
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
 }
@@ -0,0 +1,243 @@
+#![feature(async_await)]
+
+#[macro_use]
+mod common;
+
+use std::task::Poll;
+
+use aktoro_channel::*;
+use futures_util::poll;
+use futures_util::SinkExt;
+use futures_util::StreamExt;
+
+type Sender = broadcast::Sender<u8>;
+type Receiver = broadcast::Receiver<u8>;
+
+channel_helpers!(send);
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+    let mut recv2 = send.subscribe();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+    recv_is_default(&mut recv2);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    recv_ok(42, &mut recv2);
+    recv_ok(24, &mut recv2);
+    recv_empty(&mut recv2);
+
+    // SUBSCRIBING
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+
+    send_ok(42, &mut send);
+
+    let mut recv2 = send.subscribe();
+    recv_is_default(&mut recv2);
+
+    send_ok(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_ok(24, &mut recv2);
+    recv_empty(&mut recv2);
+
+    // LAGGING
+    let (mut send, mut recv) = broadcast::new::<u8>(2);
+    let mut recv2 = send.subscribe();
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+
+    recv_ok(42, &mut recv2);
+
+    send_ok(16, &mut send);
+    send_ok(8, &mut send);
+
+    recv_lagged(2, &mut recv);
+    recv_ok(16, &mut recv);
+    recv_ok(8, &mut recv);
+    recv_empty(&mut recv);
+
+    recv_lagged(1, &mut recv2);
+    recv_ok(16, &mut recv2);
+    recv_ok(8, &mut recv2);
+    recv_empty(&mut recv2);
+
+    // SINK
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+
+    assert_eq!(SinkExt::send(&mut send, 42).await, Ok(()));
+    assert_eq!(SinkExt::send(&mut send, 24).await, Ok(()));
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    // STREAM
+    let (mut send, mut recv) = broadcast::new::<u8>(2);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    send_ok(16, &mut send);
+
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(recv.next().await, Some(16));
+    assert_eq!(poll!(recv.next()), Poll::Pending);
+
+    // RECV FUTURE
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+    let mut recv2 = send.subscribe();
+
+    {
+        let mut fut = recv.recv();
+        let mut fut2 = recv2.recv();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+        assert_eq!(poll!(&mut fut2), Poll::Pending);
+
+        send_ok(42, &mut send);
+
+        assert_eq!(fut.await, Ok(42));
+        assert_eq!(fut2.await, Ok(42));
+    }
+
+    send_ok(24, &mut send);
+    assert_eq!(send.close(), Ok(()));
+
+    assert_eq!(recv.recv().await, Ok(24));
+    assert_eq!(recv.recv().await, Err(ReceiveError::Closed));
+
+    // DISCONNECTING RECV
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+    let mut recv2 = send.subscribe();
+
+    assert_eq!(recv2.disconnect(), Ok(()));
+    assert!(!recv2.is_closed());
+    assert!(recv2.is_disconnected());
+    assert_eq!(recv2.disconnect(), Err(DisconnectError::Disconnected));
+    assert_eq!(recv2.close(), Err(CloseError::Disconnected));
+
+    send_ok(42, &mut send);
+
+    recv_disconnected(&mut recv2);
+    recv_ok(42, &mut recv);
+
+    assert_eq!(recv.disconnect(), Ok(()));
+    assert!(!send.is_closed());
+
+    send_ok(24, &mut send);
+
+    // CLOSING RECV
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+    let mut recv2 = send.subscribe();
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv2.close(), Ok(()));
+    assert!(recv.is_closed());
+    assert!(send.is_closed());
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+
+    send_closed(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+    recv_ok(42, &mut recv2);
+    recv_closed(&mut recv2);
+
+    // DROPING SEND
+    let (mut send, mut recv) = broadcast::new::<u8>(8);
+    let send2 = send.clone();
+
+    send_ok(42, &mut send);
+
+    drop(send);
+    assert!(!recv.is_closed());
+    drop(send2);
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+
+    // DROPING RECV
+    let (mut send, recv) = broadcast::new::<u8>(8);
+    let recv2 = send.subscribe();
+
+    drop(recv);
+    send_ok(42, &mut send);
+
+    drop(recv2);
+    send_ok(24, &mut send);
+
+    // RESUBSCRIBING
+    let (mut send, recv) = broadcast::new::<u8>(8);
+
+    drop(recv);
+    send_ok(42, &mut send);
+
+    let mut recv = send.subscribe();
+    recv_is_default(&mut recv);
+
+    send_ok(24, &mut send);
+
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    drop(send);
+    recv_closed(&mut recv);
+
+    // CHANNEL
+    let (mut send, mut recv) = channel::broadcast::<u8>(8);
+
+    assert_eq!(send.try_send(42), Ok(()));
+    assert_eq!(send.send(24).await, Ok(()));
+    assert_eq!(
+        send.try_send_notify(16).unwrap_err(),
+        SendError::Unsupported(16)
+    );
+
+    assert_eq!(recv.try_recv(), Ok(42));
+    assert_eq!(recv.recv().await, Ok(24));
+    assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
+}
+
+fn recv_lagged(lagged: usize, recv: &mut Receiver) {
+    assert_eq!(recv.try_recv(), Err(ReceiveError::Lagged(lagged)));
+    assert!(!recv.is_closed());
+}
+
+#[derive(PartialEq, Debug)]
+struct PanickingClone;
+
+impl Clone for PanickingClone {
+    fn clone(&self) -> PanickingClone {
+        panic!("the clone failed");
+    }
+}
+
+#[test]
+fn panicking_clone() {
+    let (mut send, mut recv) = broadcast::new::<PanickingClone>(8);
+    let mut recv2 = send.subscribe();
+
+    assert_eq!(send.send(PanickingClone), Ok(()));
+
+    // NOTE: the receiver is dropped while its thread is
+    //   panicking.
+    let thread = std::thread::spawn(move || {
+        let _ = recv.try_recv();
+    });
+
+    assert!(thread.join().is_err());
+    assert_eq!(recv2.disconnect(), Ok(()));
+    assert_eq!(send.send(PanickingClone), Ok(()));
+    drop(send);
+}
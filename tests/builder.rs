@@ -0,0 +1,199 @@
+#![feature(async_await)]
+
+use aktoro_channel::*;
+use futures_util::StreamExt;
+
+type Sender = channel::Sender<u8>;
+type Receiver = channel::Receiver<u8>;
+
+#[runtime::test]
+async fn test() {
+    // DEFAULT
+    let (mut send, mut recv) = ChannelBuilder::new().build::<u8>().unwrap();
+
+    match (&send, &recv) {
+        (Sender::Unbounded(_), Receiver::Unbounded(_)) => (),
+        _ => panic!("the channel should be unbounded"),
+    }
+
+    for i in 0..16 {
+        send_ok(i, &mut send);
+    }
+
+    for i in 0..16 {
+        recv_ok(i, &mut recv);
+    }
+
+    recv_empty(&mut recv);
+    assert_eq!(recv.try_clone().unwrap_err(), CloneError::Limit);
+
+    // BOUNDED
+    let (mut send, mut recv) = ChannelBuilder::new()
+        .buf(Some(1))
+        .max_senders(Some(2))
+        .build::<u8>()
+        .unwrap();
+
+    match (&send, &recv) {
+        (Sender::Bounded(_), Receiver::Bounded(_)) => (),
+        _ => panic!("the channel should be bounded"),
+    }
+
+    let send2 = send.try_clone().unwrap();
+    assert_eq!(send.try_clone().unwrap_err(), CloneError::Limit);
+    drop(send2);
+
+    send_ok(42, &mut send);
+    send_full(24, &mut send);
+    recv_ok(42, &mut recv);
+
+    // MULTIPLE RECEIVERS
+    let (mut send, mut recv) = ChannelBuilder::new()
+        .buf(Some(8))
+        .max_receivers(Some(2))
+        .build::<u8>()
+        .unwrap();
+
+    match (&send, &recv) {
+        (Sender::Mpmc(_), Receiver::Mpmc(_)) => (),
+        _ => panic!("the channel should be multi-consumer"),
+    }
+
+    let mut recv2 = recv.try_clone().unwrap();
+    assert_eq!(recv.try_clone().unwrap_err(), CloneError::Limit);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+
+    recv_ok(42, &mut recv2);
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    // QUOTA
+    let (mut send, mut recv) = ChannelBuilder::new().quota(Some(2)).build::<u8>().unwrap();
+
+    match send {
+        Sender::Quota(_) => (),
+        _ => panic!("the channel should have a quota"),
+    }
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    assert_eq!(send.try_send(16), Err(SendError::Exhausted(16)));
+
+    assert_eq!(recv.next().await, Some(42));
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(recv.next().await, None);
+
+    // NOTIFY
+    let (mut send, mut recv) = ChannelBuilder::new()
+        .notify(true)
+        .buf(Some(1))
+        .build::<u8>()
+        .unwrap();
+
+    let receipt = send.try_send_notify(42).unwrap();
+
+    recv_ok(42, &mut recv);
+    assert_eq!(receipt.await, Ok(()));
+
+    let (mut send, mut recv) = ChannelBuilder::new()
+        .notify(false)
+        .buf(Some(1))
+        .build::<u8>()
+        .unwrap();
+
+    let err = send.try_send_notify(42).unwrap_err();
+    assert!(err.is_unsupported());
+    assert_eq!(err, SendError::Unsupported(42));
+
+    send_ok(42, &mut send);
+    recv_ok(42, &mut recv);
+
+    let (mut send, _recv) = ChannelBuilder::new().quota(Some(1)).build::<u8>().unwrap();
+
+    assert_eq!(
+        send.try_send_notify(42).unwrap_err(),
+        SendError::Unsupported(42)
+    );
+    send_ok(42, &mut send);
+
+    // DUPLEX
+    let (mut a, mut b) = ChannelBuilder::new()
+        .bidirectional(true)
+        .buf(Some(1))
+        .build_duplex::<u8, u16>()
+        .unwrap();
+
+    assert_eq!(a.try_send(42), Ok(()));
+    assert_eq!(a.try_send(24), Err(SendError::Full(24)));
+    assert_eq!(b.try_send(4242), Ok(()));
+
+    assert_eq!(b.try_recv(), Ok(42));
+    assert_eq!(a.try_recv(), Ok(4242));
+
+    assert_eq!(a.close(), Ok(()));
+    assert!(b.is_closed());
+
+    // ERRORS
+    build_err(
+        ChannelBuilder::new().max_senders(Some(0)),
+        BuildError::NoSenderOrReceiver,
+    );
+    build_err(
+        ChannelBuilder::new().max_receivers(Some(0)),
+        BuildError::NoSenderOrReceiver,
+    );
+    build_err(
+        ChannelBuilder::new().bidirectional(true),
+        BuildError::Direction,
+    );
+    build_err(
+        ChannelBuilder::new().quota(Some(1)).buf(Some(1)),
+        BuildError::Quota,
+    );
+    build_err(
+        ChannelBuilder::new().quota(Some(1)).max_receivers(None),
+        BuildError::Quota,
+    );
+    build_err(
+        ChannelBuilder::new().buf(Some(0)).max_receivers(None),
+        BuildError::Receivers,
+    );
+
+    duplex_err(ChannelBuilder::new(), BuildError::Direction);
+    duplex_err(
+        ChannelBuilder::new().bidirectional(true),
+        BuildError::Unbounded,
+    );
+
+    let builder = ChannelBuilder::new().bidirectional(true).buf(Some(1));
+
+    duplex_err(builder.clone().quota(Some(1)), BuildError::Quota);
+    duplex_err(builder.clone().notify(true), BuildError::Notify);
+    duplex_err(builder.max_receivers(None), BuildError::Receivers);
+}
+
+fn send_ok(data: u8, send: &mut Sender) {
+    assert_eq!(send.try_send(data), Ok(()));
+}
+
+fn send_full(data: u8, send: &mut Sender) {
+    assert_eq!(send.try_send(data), Err(SendError::Full(data)));
+}
+
+fn recv_ok(data: u8, recv: &mut Receiver) {
+    assert_eq!(recv.try_recv(), Ok(data));
+}
+
+fn recv_empty(recv: &mut Receiver) {
+    assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
+}
+
+fn build_err(builder: ChannelBuilder, err: BuildError) {
+    assert_eq!(builder.build::<u8>().unwrap_err(), err);
+}
+
+fn duplex_err(builder: ChannelBuilder, err: BuildError) {
+    assert_eq!(builder.build_duplex::<u8, u8>().unwrap_err(), err);
+}
@@ -0,0 +1,125 @@
+#![allow(unused_macros)]
+
+/// Defines the helpers asserting the state of the halves
+/// of a channel that can hold several messages (whose
+/// types are aliased as `Sender` and `Receiver`), sending
+/// messages with the senders' `$send` method.
+macro_rules! channel_helpers {
+    ($send:ident) => {
+        // NOTE: every test only uses some of the helpers.
+        #[allow(dead_code)]
+        mod helpers {
+            use super::*;
+
+            pub fn send_is_default(send: &Sender) {
+                assert!(!send.is_closed());
+                assert!(!send.is_disconnected());
+            }
+
+            pub fn recv_is_default(recv: &mut Receiver) {
+                assert!(!recv.is_closed());
+                assert!(!recv.is_disconnected());
+                recv_empty(recv);
+            }
+
+            pub fn send_ok(data: u8, send: &mut Sender) {
+                assert_eq!(send.$send(data), Ok(()));
+                assert!(!send.is_closed());
+                assert!(!send.is_disconnected());
+            }
+
+            pub fn send_full(data: u8, send: &mut Sender) {
+                assert_eq!(send.$send(data), Err(SendError::Full(data)));
+                assert!(!send.is_closed());
+                assert!(!send.is_disconnected());
+            }
+
+            pub fn send_disconnected(data: u8, send: &mut Sender) {
+                assert_eq!(send.$send(data), Err(SendError::Disconnected(data)));
+                assert!(send.is_disconnected());
+            }
+
+            pub fn send_closed(data: u8, send: &mut Sender) {
+                assert_eq!(send.$send(data), Err(SendError::Closed(data)));
+                assert!(send.is_closed());
+                assert!(!send.is_disconnected());
+            }
+
+            pub fn recv_ok(data: u8, recv: &mut Receiver) {
+                assert_eq!(recv.try_recv(), Ok(data));
+            }
+
+            pub fn recv_empty(recv: &mut Receiver) {
+                assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
+                assert!(!recv.is_closed());
+            }
+
+            pub fn recv_disconnected(recv: &mut Receiver) {
+                assert_eq!(recv.try_recv(), Err(ReceiveError::Disconnected));
+                assert!(recv.is_disconnected());
+            }
+
+            pub fn recv_closed(recv: &mut Receiver) {
+                assert_eq!(recv.try_recv(), Err(ReceiveError::Closed));
+                assert!(recv.is_closed());
+            }
+        }
+
+        use helpers::*;
+    };
+}
+
+/// Defines the helpers asserting the state of the halves
+/// of a single use channel (whose types are aliased as
+/// `Sender` and `Receiver`).
+macro_rules! single_use_helpers {
+    () => {
+        // NOTE: every test only uses some of the helpers.
+        #[allow(dead_code)]
+        mod helpers {
+            use super::*;
+
+            pub fn send_is_default(send: &Sender) {
+                assert!(!send.sent);
+                assert!(!send.cancelled);
+                assert!(!send.closed);
+                assert!(!send.is_cancelled());
+            }
+
+            pub fn recv_is_default(recv: &mut Receiver) {
+                assert!(!recv.received);
+                assert!(!recv.closed);
+                assert!(!recv.cancelled);
+                assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
+            }
+
+            pub fn send_ok(data: u8, send: &mut Sender) {
+                assert_eq!(send.send(data), Ok(()));
+                assert!(send.sent);
+                assert!(!send.cancelled);
+            }
+
+            pub fn send_full(data: u8, send: &mut Sender) {
+                assert_eq!(send.send(data), Err(SendError::Full(data)));
+                assert!(send.sent);
+                assert!(!send.cancelled);
+            }
+
+            pub fn send_closed(data: u8, send: &mut Sender) {
+                let sent = send.sent;
+                assert_eq!(send.send(data), Err(SendError::Closed(data)));
+                assert_eq!(send.sent, sent);
+                assert!(send.cancelled);
+            }
+
+            pub fn recv_ok(data: u8, recv: &mut Receiver) {
+                assert_eq!(recv.try_recv(), Ok(data));
+                assert!(recv.received);
+                assert!(!recv.closed);
+                assert!(!recv.cancelled);
+            }
+        }
+
+        use helpers::*;
+    };
+}
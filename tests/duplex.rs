@@ -0,0 +1,110 @@
+#![feature(async_await)]
+
+use std::task::Poll;
+
+use aktoro_channel::*;
+use futures_util::poll;
+use futures_util::SinkExt;
+use futures_util::StreamExt;
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut left, mut right) = duplex::new::<u8, &str>(8, 8);
+
+    assert!(!left.is_closed());
+    assert!(!right.is_closed());
+
+    assert_eq!(left.try_send(42), Ok(()));
+    assert_eq!(right.try_send("42"), Ok(()));
+
+    assert_eq!(right.try_recv(), Ok(42));
+    assert_eq!(left.try_recv(), Ok("42"));
+    assert_eq!(right.try_recv(), Err(ReceiveError::Empty));
+    assert_eq!(left.try_recv(), Err(ReceiveError::Empty));
+
+    // CAPACITIES
+    let (mut left, mut right) = duplex::new::<u8, u8>(1, 2);
+
+    assert_eq!(left.try_send(42), Ok(()));
+    assert_eq!(left.try_send(24), Err(SendError::Full(24)));
+
+    assert_eq!(right.try_send(16), Ok(()));
+    assert_eq!(right.try_send(8), Ok(()));
+    assert_eq!(right.try_send(4), Err(SendError::Full(4)));
+
+    assert_eq!(right.try_recv(), Ok(42));
+    assert_eq!(left.try_recv(), Ok(16));
+    assert_eq!(left.try_recv(), Ok(8));
+
+    // FUTURES
+    let (mut left, mut right) = duplex::new::<u8, u8>(1, 1);
+
+    assert_eq!(left.send(42).await, Ok(()));
+
+    {
+        let mut fut = left.send(24);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(right.recv().await, Ok(42));
+
+        assert_eq!(fut.await, Ok(()));
+    }
+
+    assert_eq!(right.recv().await, Ok(24));
+
+    // SINK AND STREAM
+    let (mut left, mut right) = duplex::new::<u8, u8>(8, 8);
+
+    assert_eq!(SinkExt::send(&mut left, 42).await, Ok(()));
+    assert_eq!(SinkExt::send(&mut right, 24).await, Ok(()));
+
+    assert_eq!(right.next().await, Some(42));
+    assert_eq!(left.next().await, Some(24));
+    assert_eq!(poll!(left.next()), Poll::Pending);
+
+    // CLOSING
+    let (mut left, mut right) = duplex::new::<u8, u8>(8, 8);
+
+    assert_eq!(left.try_send(42), Ok(()));
+    assert_eq!(right.try_send(24), Ok(()));
+
+    assert_eq!(left.close(), Ok(()));
+    assert!(left.is_closed());
+    assert!(right.is_closed());
+    assert_eq!(left.close(), Err(CloseError::Closed));
+    assert_eq!(right.close(), Err(CloseError::Closed));
+
+    assert_eq!(left.try_send(16), Err(SendError::Closed(16)));
+    assert_eq!(right.try_send(8), Err(SendError::Closed(8)));
+
+    assert_eq!(right.try_recv(), Ok(42));
+    assert_eq!(left.try_recv(), Ok(24));
+    assert_eq!(right.try_recv(), Err(ReceiveError::Closed));
+    assert_eq!(left.next().await, None);
+
+    // DROPING
+    let (mut left, right) = duplex::new::<u8, u8>(8, 8);
+
+    {
+        let mut fut = left.recv();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        drop(right);
+
+        assert_eq!(fut.await, Err(ReceiveError::Closed));
+    }
+
+    assert!(left.is_closed());
+    assert_eq!(left.try_send(42), Err(SendError::Closed(42)));
+
+    // SPLITTING
+    let (left, mut right) = duplex::new::<u8, u8>(8, 8);
+    let (mut send, mut recv) = left.split();
+
+    assert_eq!(send.try_send(42), Ok(()));
+    assert_eq!(right.try_send(24), Ok(()));
+
+    assert_eq!(right.try_recv(), Ok(42));
+    assert_eq!(recv.try_recv(), Ok(24));
+}
@@ -0,0 +1,204 @@
+#![feature(async_await)]
+
+#[macro_use]
+mod common;
+
+use std::task::Poll;
+
+use aktoro_channel::*;
+use futures_util::poll;
+use futures_util::StreamExt;
+
+type Sender = mpmc::Sender<u8>;
+type Receiver = mpmc::Receiver<u8>;
+
+channel_helpers!(try_send);
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = mpmc::bounded::<u8>(8);
+    let mut recv2 = recv.clone();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+    recv_is_default(&mut recv2);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    send_ok(16, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv2);
+    recv_ok(16, &mut recv);
+    recv_empty(&mut recv);
+    recv_empty(&mut recv2);
+
+    // FULL
+    let (mut send, mut recv) = mpmc::bounded::<u8>(1);
+    let mut recv2 = recv.clone();
+
+    send_ok(42, &mut send);
+    send_full(24, &mut send);
+
+    recv_ok(42, &mut recv2);
+
+    send_ok(16, &mut send);
+    send_full(8, &mut send);
+
+    recv_ok(16, &mut recv);
+    recv_empty(&mut recv2);
+
+    // FUTURES
+    let (mut send, mut recv) = mpmc::unbounded::<u8>();
+    let mut recv2 = recv.clone();
+
+    {
+        let mut fut = recv.recv();
+        let mut fut2 = recv2.recv();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+        assert_eq!(poll!(&mut fut2), Poll::Pending);
+
+        assert_eq!(send.send(42).await, Ok(()));
+
+        assert_eq!(fut.await, Ok(42));
+        assert_eq!(poll!(&mut fut2), Poll::Pending);
+    }
+
+    send_ok(24, &mut send);
+    assert_eq!(recv2.next().await, Some(24));
+
+    // DISCONNECTING RECV
+    let (mut send, mut recv) = mpmc::bounded::<u8>(8);
+
+    send_ok(42, &mut send);
+
+    {
+        let mut recv = recv.clone();
+
+        assert_eq!(recv.disconnect(), Ok(()));
+        assert!(!recv.is_closed());
+        assert!(recv.is_disconnected());
+        assert_eq!(recv.disconnect(), Err(DisconnectError::Disconnected));
+
+        assert_eq!(recv.close(), Err(CloseError::Disconnected));
+
+        recv_disconnected(&mut recv);
+        assert_eq!(recv.recv().await, Err(ReceiveError::Disconnected));
+        assert_eq!(recv.clone().try_recv(), Err(ReceiveError::Disconnected));
+    }
+
+    assert!(!recv.is_closed());
+    assert!(!recv.is_disconnected());
+
+    send_ok(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+
+    assert_eq!(recv.disconnect(), Ok(()));
+    assert!(recv.is_closed());
+
+    send_closed(16, &mut send);
+
+    // LIMITS
+    let (mut send, recv) = mpmc::unbounded_with_limits::<u8>(Some(1), Some(2));
+
+    assert_eq!(send.try_clone().unwrap_err(), CloneError::Limit);
+
+    let mut recv2 = recv.try_clone().unwrap();
+    assert_eq!(recv2.try_clone().unwrap_err(), CloneError::Limit);
+
+    send_ok(42, &mut send);
+    recv_ok(42, &mut recv2);
+
+    drop(recv);
+
+    let mut recv = recv2.try_clone().unwrap();
+
+    send_ok(24, &mut send);
+    recv_ok(24, &mut recv);
+
+    // CLOSING RECV
+    let (mut send, mut recv) = mpmc::unbounded::<u8>();
+    let mut recv2 = recv.clone();
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv2.close(), Ok(()));
+    assert!(recv.is_closed());
+    assert!(send.is_closed());
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+
+    send_closed(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+    recv_closed(&mut recv2);
+
+    // DROPING RECV
+    let (mut send, recv) = mpmc::bounded::<u8>(8);
+    let recv2 = recv.clone();
+
+    send_ok(42, &mut send);
+
+    drop(recv);
+
+    send_ok(24, &mut send);
+    assert!(!send.is_closed());
+
+    drop(recv2);
+
+    send_closed(16, &mut send);
+
+    // DROPING SEND
+    let (mut send, mut recv) = mpmc::bounded::<u8>(8);
+    let mut recv2 = recv.clone();
+
+    send_ok(42, &mut send);
+
+    drop(send);
+
+    recv_ok(42, &mut recv2);
+    recv_closed(&mut recv);
+    recv_closed(&mut recv2);
+}
+
+#[test]
+fn threads() {
+    let (mut send, recv) = mpmc::bounded::<u32>(16);
+
+    let threads = (0..4)
+        .map(|_| {
+            let mut recv = recv.clone();
+            std::thread::spawn(move || {
+                let mut received = vec![];
+                loop {
+                    match recv.try_recv() {
+                        Ok(data) => received.push(data),
+                        Err(ReceiveError::Empty) => std::thread::yield_now(),
+                        Err(_) => return received,
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    drop(recv);
+    for i in 0..4000 {
+        let mut data = i;
+        while let Err(SendError::Full(d)) = send.try_send(data) {
+            data = d;
+            std::thread::yield_now();
+        }
+    }
+    drop(send);
+
+    let mut received = threads
+        .into_iter()
+        .flat_map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+
+    received.sort();
+    assert_eq!(received, (0..4000).collect::<Vec<_>>());
+}
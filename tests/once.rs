@@ -1,6 +1,10 @@
 #![feature(async_await)]
 
+#[macro_use]
+mod common;
+
 use std::task::Poll;
+use std::time::Duration;
 
 use aktoro_channel::*;
 use futures_util::poll;
@@ -8,6 +12,8 @@ use futures_util::poll;
 type Sender = once::Sender<u8>;
 type Receiver = once::Receiver<u8>;
 
+single_use_helpers!();
+
 #[runtime::test]
 async fn test() {
     // NORMAL
@@ -53,70 +59,91 @@ async fn test() {
     send_closed(42, &mut send);
     recv_closed(&mut recv);
 
-    // DROPING SEND
-    let (send, mut recv) = once::new::<u8>();
+    // CLOSING SEND
+    let (mut send, mut recv) = once::new::<u8>();
 
     send_is_default(&send);
     recv_is_default(&mut recv);
 
-    drop(send);
+    assert_eq!(send.close(), Ok(()));
+    assert!(send.closed);
+    assert!(!send.is_cancelled());
+    assert_eq!(send.close(), Err(CloseError::Closed));
 
+    assert_eq!(send.send(42), Err(SendError::Closed(42)));
     recv_closed(&mut recv);
 
-    // DROPING RECV
+    // CANCELLATION
     let (mut send, mut recv) = once::new::<u8>();
 
     send_is_default(&send);
     recv_is_default(&mut recv);
 
-    drop(recv);
+    {
+        let mut fut = send.cancelled();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(recv.close(), Ok(()));
+
+        fut.await;
+    }
+
+    assert!(send.is_cancelled());
+    assert!(send.cancelled);
+    assert_eq!(send.close(), Err(CloseError::Closed));
 
     send_closed(42, &mut send);
-}
 
-fn send_is_default(send: &Sender) {
-    assert!(!send.sent);
-    assert!(!send.cancelled);
-}
+    let (mut send, recv) = once::new::<u8>();
 
-fn recv_is_default(recv: &mut Receiver) {
-    assert!(!recv.received);
-    assert!(!recv.closed);
-    assert!(!recv.cancelled);
-    recv_empty(recv);
-}
+    assert_eq!(poll!(send.cancelled()), Poll::Pending);
+    drop(recv);
 
-fn send_ok(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Ok(()));
-    assert!(send.sent);
+    assert!(send.is_cancelled());
     assert!(!send.cancelled);
-}
+    send.cancelled().await;
+    assert!(send.cancelled);
 
-fn send_full(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Err(SendError::Full(data)));
-    assert!(send.sent);
-    assert!(!send.cancelled);
-}
+    let (mut send, _recv) = once::new::<u8>();
 
-fn send_closed(data: u8, send: &mut Sender) {
-    let sent = send.sent;
-    assert_eq!(send.send(data), Err(SendError::Closed(data)));
-    assert_eq!(send.sent, sent);
-    assert!(send.cancelled);
-}
+    send_ok(42, &mut send);
+    assert!(!send.is_cancelled());
+    assert_eq!(send.close(), Err(CloseError::Closed));
+    send.cancelled().await;
 
-fn recv_ok(data: u8, recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Ok(data));
-    assert!(recv.received);
-    assert!(!recv.closed);
-    assert!(!recv.cancelled);
+    // DROPING SEND
+    let (send, mut recv) = once::new::<u8>();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    drop(send);
+
+    recv_sender_dropped(&mut recv);
+    recv_sender_dropped(&mut recv);
+
+    let (send, recv) = once::new::<u8>();
+
+    drop(send);
+
+    assert_eq!(recv.await, Err(ReceiveError::SenderDropped));
+
+    // DROPING RECV
+    let (mut send, mut recv) = once::new::<u8>();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    drop(recv);
+
+    send_closed(42, &mut send);
 }
 
-fn recv_empty(recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
+fn recv_sender_dropped(recv: &mut Receiver) {
+    assert_eq!(recv.try_recv(), Err(ReceiveError::SenderDropped));
     assert!(!recv.received);
     assert!(!recv.closed);
-    assert!(!recv.cancelled);
+    assert!(recv.cancelled);
 }
 
 fn recv_closed(recv: &mut Receiver) {
@@ -125,3 +152,51 @@ fn recv_closed(recv: &mut Receiver) {
     assert_eq!(recv.received, received);
     assert!(recv.closed || recv.cancelled);
 }
+
+#[test]
+fn wait() {
+    let (mut send, mut recv) = once::new::<u8>();
+
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(send.send(42), Ok(()));
+    });
+
+    assert_eq!(recv.wait(), Ok(42));
+    assert!(recv.received);
+    thread.join().unwrap();
+
+    let (send, mut recv) = once::new::<u8>();
+
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        drop(send);
+    });
+
+    assert_eq!(recv.wait(), Err(ReceiveError::SenderDropped));
+    thread.join().unwrap();
+
+    let (mut send, mut recv) = once::new::<u8>();
+
+    assert_eq!(
+        recv.wait_timeout(Duration::from_millis(10)),
+        Err(ReceiveError::Timeout)
+    );
+
+    assert_eq!(send.send(42), Ok(()));
+    assert_eq!(recv.wait_timeout(Duration::from_millis(10)), Ok(42));
+}
+
+#[test]
+fn panicking() {
+    let (send, mut recv) = once::new::<u8>();
+
+    let thread = std::thread::spawn(move || {
+        let _send = send;
+        panic!("the reply couldn't be computed");
+    });
+
+    assert!(thread.join().is_err());
+    assert_eq!(recv.wait(), Err(ReceiveError::SenderPanicked));
+    assert!(recv.try_recv().unwrap_err().is_sender_dropped());
+}
@@ -0,0 +1,303 @@
+#![feature(async_await)]
+
+#[macro_use]
+mod common;
+
+use std::task::Poll;
+use std::time::Duration;
+
+use aktoro_channel::*;
+use futures_util::poll;
+use futures_util::SinkExt;
+use futures_util::StreamExt;
+
+type Sender = priority::Sender<u8>;
+type Receiver = priority::Receiver<u8>;
+
+channel_helpers!(try_send);
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = priority::new::<u8>(&[8, 8]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(send.lanes(), 2);
+    assert_eq!(send.lane(), 1);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    // LANES
+    let (mut send, mut recv) = priority::new::<u8>(&[8, 8, 8]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(send.try_send_to(2, 1), Ok(()));
+    assert_eq!(send.try_send_to(1, 2), Ok(()));
+    assert_eq!(send.try_send_to(2, 3), Ok(()));
+    assert_eq!(send.try_send_to(0, 4), Ok(()));
+    assert_eq!(send.try_send_to(1, 5), Ok(()));
+
+    recv_ok(4, &mut recv);
+    recv_ok(2, &mut recv);
+    recv_ok(5, &mut recv);
+    recv_ok(1, &mut recv);
+    recv_ok(3, &mut recv);
+    recv_empty(&mut recv);
+
+    // BOUND SENDERS
+    let (mut user, mut recv) = priority::new::<u8>(&[8, 8]);
+    let mut system = user.bind(0);
+
+    send_is_default(&user);
+    send_is_default(&system);
+    recv_is_default(&mut recv);
+
+    assert_eq!(system.lane(), 0);
+
+    send_ok(42, &mut user);
+    send_ok(24, &mut user);
+    send_ok(16, &mut system);
+
+    recv_ok(16, &mut recv);
+    recv_ok(42, &mut recv);
+
+    send_ok(8, &mut system);
+
+    recv_ok(8, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    // FULL LANES
+    let (mut send, mut recv) = priority::new::<u8>(&[1, 2]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+    send_full(16, &mut send);
+
+    assert_eq!(send.try_send_to(0, 8), Ok(()));
+    assert_eq!(send.try_send_to(0, 4), Err(SendError::Full(4)));
+
+    recv_ok(8, &mut recv);
+    assert_eq!(send.try_send_to(0, 4), Ok(()));
+    send_full(16, &mut send);
+
+    recv_ok(4, &mut recv);
+    recv_ok(42, &mut recv);
+    send_ok(16, &mut send);
+
+    recv_ok(24, &mut recv);
+    recv_ok(16, &mut recv);
+    recv_empty(&mut recv);
+
+    // AGING
+    let (mut send, mut recv) = priority::with_aging::<u8>(&[8, 8], 2);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    for i in 10..16 {
+        assert_eq!(send.try_send_to(0, i), Ok(()));
+    }
+
+    send_ok(1, &mut send);
+    send_ok(2, &mut send);
+
+    for i in &[10, 11, 1, 12, 13, 2, 14, 15] {
+        recv_ok(*i, &mut recv);
+    }
+
+    recv_empty(&mut recv);
+
+    // SEND FUTURE
+    let (mut send, mut recv) = priority::new::<u8>(&[1, 1]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    {
+        let mut fut = send.send(24);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        recv_ok(42, &mut recv);
+
+        assert_eq!(fut.await, Ok(()));
+    }
+
+    assert_eq!(send.send_to(0, 16).await, Ok(()));
+
+    {
+        let mut fut = send.send_to(0, 8);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(recv.close(), Ok(()));
+
+        assert_eq!(fut.await, Err(SendError::Closed(8)));
+    }
+
+    recv_ok(16, &mut recv);
+    recv_ok(24, &mut recv);
+    recv_closed(&mut recv);
+
+    // RECEIPTS
+    let (mut send, mut recv) = priority::new::<u8>(&[1]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    let mut receipt = send.try_send_notify(42).unwrap();
+    assert_eq!(poll!(&mut receipt), Poll::Pending);
+
+    assert_eq!(send.try_send_notify(24).unwrap_err(), SendError::Full(24));
+
+    recv_ok(42, &mut recv);
+    assert_eq!(receipt.await, Ok(()));
+
+    let receipt = send.try_send_notify(24).unwrap();
+    drop(recv);
+    assert_eq!(receipt.await, Err(ReceiveError::Closed));
+
+    // SINK
+    let (mut send, mut recv) = priority::new::<u8>(&[8, 1]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(SinkExt::send(&mut send, 42).await, Ok(()));
+    assert_eq!(poll!(SinkExt::send(&mut send, 24)), Poll::Pending);
+
+    recv_ok(42, &mut recv);
+
+    assert_eq!(SinkExt::flush(&mut send).await, Ok(()));
+    recv_ok(24, &mut recv);
+    recv_empty(&mut recv);
+
+    // STREAM
+    let (mut send, mut recv) = priority::new::<u8>(&[8, 8]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+    assert_eq!(send.try_send_to(0, 24), Ok(()));
+
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(recv.next().await, Some(42));
+    assert_eq!(poll!(recv.next()), Poll::Pending);
+
+    drop(send);
+    assert_eq!(recv.next().await, None);
+
+    // CHANNEL
+    let (mut send, mut recv) = channel::priority::<u8>(&[8, 8]);
+
+    assert_eq!(send.try_send(42), Ok(()));
+    assert!(send.try_clone().is_ok());
+    assert_eq!(recv.try_clone().unwrap_err(), CloneError::Limit);
+
+    if let channel::Sender::Priority(send) = &mut send {
+        assert_eq!(send.try_send_to(0, 24), Ok(()));
+    }
+
+    assert_eq!(recv.recv().await, Ok(24));
+    assert_eq!(recv.recv().await, Ok(42));
+
+    // DISCONNECTING SEND
+    let (mut send, mut recv) = priority::new::<u8>(&[8]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    {
+        let mut send = send.clone();
+
+        send_ok(42, &mut send);
+
+        assert_eq!(send.disconnect(), Ok(()));
+        assert!(!send.is_closed());
+        assert_eq!(send.disconnect(), Err(DisconnectError::Disconnected));
+        assert_eq!(send.close(), Err(CloseError::Disconnected));
+
+        send_disconnected(24, &mut send);
+    }
+
+    send_is_default(&send);
+
+    assert_eq!(send.disconnect(), Ok(()));
+    assert!(recv.is_closed());
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+
+    // CLOSING RECV
+    let (mut send, mut recv) = priority::new::<u8>(&[8, 8]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv.close(), Ok(()));
+    assert!(send.is_closed());
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+
+    send_closed(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+
+    // DISCONNECTING RECV
+    let (mut send, mut recv) = priority::new::<u8>(&[8]);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv.disconnect(), Ok(()));
+    assert!(recv.is_disconnected());
+    assert_eq!(recv.disconnect(), Err(DisconnectError::Disconnected));
+
+    recv_disconnected(&mut recv);
+    assert_eq!(recv.next().await, None);
+
+    send_closed(24, &mut send);
+}
+
+#[test]
+fn blocking() {
+    let (mut send, mut recv) = priority::new::<u32>(&[1, 1]);
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..1000 {
+            assert_eq!(send.send_blocking(i), Ok(()));
+        }
+
+        send
+    });
+
+    for i in 0..1000 {
+        assert_eq!(recv.recv_blocking(), Ok(i));
+    }
+
+    let mut send = thread.join().unwrap();
+
+    assert_eq!(send.send_timeout(42, Duration::from_millis(10)), Ok(()));
+    assert_eq!(
+        send.send_timeout(24, Duration::from_millis(10)),
+        Err(SendError::Timeout(24))
+    );
+}
@@ -0,0 +1,192 @@
+#![feature(async_await)]
+
+#[macro_use]
+mod common;
+
+use std::task::Poll;
+use std::time::Duration;
+
+use aktoro_channel::*;
+use futures_util::poll;
+
+type Sender = promise::Sender<u8>;
+type Receiver = promise::Receiver<u8>;
+
+single_use_helpers!();
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = promise::new::<u8>();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    let mut recv2 = recv.clone();
+    recv_is_default(&mut recv2);
+
+    send_ok(42, &mut send);
+    send_full(24, &mut send);
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+    recv_ok(42, &mut recv2);
+
+    // LATE SUBSCRIBERS
+    let mut recv3 = recv.clone();
+    recv_ok(42, &mut recv3);
+
+    assert_eq!(recv2.clone().await, Ok(42));
+
+    // USING FUTURE
+    let (mut send, mut recv) = promise::new::<u8>();
+    let mut recv2 = recv.clone();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(poll!(&mut recv), Poll::Pending);
+    assert_eq!(poll!(&mut recv2), Poll::Pending);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(poll!(&mut recv), Poll::Ready(Ok(42)));
+    assert_eq!(recv2.await, Ok(42));
+    assert!(recv.received);
+
+    recv_closed(&mut recv);
+
+    // CLOSING RECV
+    let (mut send, mut recv) = promise::new::<u8>();
+    let recv2 = recv.clone();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(recv.close(), Ok(()));
+    assert!(recv.closed);
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+    assert!(!send.is_cancelled());
+
+    recv_closed(&mut recv);
+
+    {
+        let mut fut = send.cancelled();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        drop(recv2);
+
+        fut.await;
+    }
+
+    assert!(send.is_cancelled());
+    send_closed(42, &mut send);
+
+    // CLOSING SEND
+    let (mut send, mut recv) = promise::new::<u8>();
+    let recv2 = recv.clone();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    assert_eq!(send.close(), Ok(()));
+    assert!(send.closed);
+    assert_eq!(send.close(), Err(CloseError::Closed));
+
+    assert_eq!(send.send(42), Err(SendError::Closed(42)));
+    recv_closed(&mut recv);
+    assert_eq!(recv2.await, Err(ReceiveError::Closed));
+
+    // DROPING SEND
+    let (send, mut recv) = promise::new::<u8>();
+    let recv2 = recv.clone();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    drop(send);
+
+    assert_eq!(recv.try_recv(), Err(ReceiveError::SenderDropped));
+    assert!(recv.cancelled);
+    assert_eq!(recv2.await, Err(ReceiveError::SenderDropped));
+
+    // DROPING RECV
+    let (mut send, recv) = promise::new::<u8>();
+
+    send_is_default(&send);
+
+    drop(recv);
+
+    assert!(send.is_cancelled());
+    send_closed(42, &mut send);
+}
+
+fn recv_closed(recv: &mut Receiver) {
+    assert_eq!(recv.try_recv(), Err(ReceiveError::Closed));
+    assert!(recv.received || recv.closed || recv.cancelled);
+}
+
+#[test]
+fn wait() {
+    let (mut send, recv) = promise::new::<u32>();
+
+    let threads = (0..4)
+        .map(|_| {
+            let mut recv = recv.clone();
+            std::thread::spawn(move || assert_eq!(recv.wait(), Ok(42)))
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(send.send(42), Ok(()));
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let (send, mut recv) = promise::new::<u32>();
+
+    let thread = std::thread::spawn(move || {
+        let _send = send;
+        panic!("the initialisation failed");
+    });
+
+    assert!(thread.join().is_err());
+    assert_eq!(recv.wait(), Err(ReceiveError::SenderPanicked));
+
+    let (mut send, mut recv) = promise::new::<u32>();
+
+    assert_eq!(
+        recv.wait_timeout(Duration::from_millis(10)),
+        Err(ReceiveError::Timeout)
+    );
+
+    assert_eq!(send.send(42), Ok(()));
+    assert_eq!(recv.wait_timeout(Duration::from_millis(10)), Ok(42));
+}
+
+#[derive(PartialEq, Debug)]
+struct PanickingClone;
+
+impl Clone for PanickingClone {
+    fn clone(&self) -> PanickingClone {
+        panic!("the clone failed");
+    }
+}
+
+#[test]
+fn panicking_clone() {
+    let (mut send, mut recv) = promise::new::<PanickingClone>();
+    let mut recv2 = recv.clone();
+
+    assert_eq!(send.send(PanickingClone), Ok(()));
+
+    // NOTE: the receiver is dropped while its thread is
+    //   panicking.
+    let thread = std::thread::spawn(move || {
+        let _ = recv.try_recv();
+    });
+
+    assert!(thread.join().is_err());
+    assert_eq!(recv2.close(), Ok(()));
+    drop(send);
+}
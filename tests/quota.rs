@@ -0,0 +1,100 @@
+#![feature(async_await)]
+
+#[macro_use]
+mod common;
+
+use aktoro_channel::*;
+use futures_util::SinkExt;
+use futures_util::StreamExt;
+
+type Sender = quota::Sender<u8>;
+type Receiver = quota::Receiver<u8>;
+
+channel_helpers!(send);
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = quota::new::<u8>(3);
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+    assert_eq!(send.quota, 3);
+    assert_eq!(send.remaining(), 3);
+
+    send_ok(42, &mut send);
+    assert_eq!(send.remaining(), 2);
+
+    recv_ok(42, &mut recv);
+    recv_empty(&mut recv);
+
+    send_ok(24, &mut send);
+    assert_eq!(send.remaining(), 1);
+
+    assert_eq!(send.send(16), Ok(()));
+    assert_eq!(send.remaining(), 0);
+    assert!(send.is_closed());
+    assert!(recv.is_closed());
+
+    send_exhausted(8, &mut send);
+
+    recv_ok(24, &mut recv);
+    recv_ok(16, &mut recv);
+    recv_closed(&mut recv);
+
+    // SHARED QUOTA
+    let (mut send, mut recv) = quota::new::<u8>(2);
+    let mut send2 = send.clone();
+
+    send_ok(42, &mut send);
+    assert_eq!(send2.remaining(), 1);
+
+    assert_eq!(send2.send(24), Ok(()));
+    assert_eq!(send.remaining(), 0);
+
+    send_exhausted(16, &mut send);
+    send_exhausted(8, &mut send2);
+
+    assert_eq!(recv.next().await, Some(42));
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(recv.next().await, None);
+
+    // EMPTY QUOTA
+    let (mut send, mut recv) = quota::new::<u8>(0);
+
+    assert!(send.is_closed());
+    send_exhausted(42, &mut send);
+    recv_closed(&mut recv);
+
+    // SINK
+    let (mut send, mut recv) = quota::new::<u8>(1);
+
+    assert_eq!(SinkExt::send(&mut send, 42).await, Ok(()));
+    assert_eq!(
+        SinkExt::send(&mut send, 24).await,
+        Err(SendError::Exhausted(24))
+    );
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+
+    // CLOSING RECV
+    let (mut send, mut recv) = quota::new::<u8>(8);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv.close(), Ok(()));
+    assert!(send.is_closed());
+
+    assert_eq!(send.send(24), Err(SendError::Closed(24)));
+    assert_eq!(send.remaining(), 7);
+
+    recv_ok(42, &mut recv);
+    recv_closed(&mut recv);
+}
+
+fn send_exhausted(data: u8, send: &mut Sender) {
+    assert_eq!(send.send(data), Err(SendError::Exhausted(data)));
+    assert_eq!(send.remaining(), 0);
+    assert!(send.is_closed());
+}
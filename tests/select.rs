@@ -0,0 +1,168 @@
+#![feature(async_await)]
+
+use std::task::Poll;
+
+use aktoro_channel::select::*;
+use aktoro_channel::*;
+use futures_executor::block_on;
+use futures_util::poll;
+use futures_util::StreamExt;
+
+#[derive(PartialEq, Eq, Debug)]
+enum Msg {
+    Mailbox(u8),
+    Control(&'static str),
+    Reply(u16),
+}
+
+#[test]
+fn test() {
+    // NOTE: a select isn't `Send`, so it can't be held
+    //   across the awaits of a `runtime::test`.
+    block_on(select());
+}
+
+async fn select() {
+    // NORMAL
+    let (mut mailbox_send, mut mailbox) = bounded::new::<u8>(8);
+    let (mut control_send, mut control) = unbounded::new::<&'static str>();
+    let (mut reply_send, mut reply) = once::new::<u16>();
+
+    let mut select = Select::new(Fairness::Biased)
+        .recv(&mut mailbox, Msg::Mailbox)
+        .recv(&mut control, Msg::Control)
+        .recv(&mut reply, Msg::Reply);
+
+    assert_eq!(select.remaining(), 3);
+    assert_eq!(poll!(select.select()), Poll::Pending);
+
+    assert_eq!(control_send.send("stop"), Ok(()));
+    assert_eq!(select.select().await, Some((1, Ok(Msg::Control("stop")))));
+
+    assert_eq!(reply_send.send(4242), Ok(()));
+    assert_eq!(mailbox_send.try_send(42), Ok(()));
+
+    assert_eq!(select.select().await, Some((0, Ok(Msg::Mailbox(42)))));
+    assert_eq!(select.select().await, Some((2, Ok(Msg::Reply(4242)))));
+    assert_eq!(select.remaining(), 2);
+
+    // CLOSED BRANCHES
+    drop(mailbox_send);
+
+    assert_eq!(select.select().await, Some((0, Err(ReceiveError::Closed))));
+    assert_eq!(select.remaining(), 1);
+
+    drop(control_send);
+
+    assert_eq!(select.select().await, Some((1, Err(ReceiveError::Closed))));
+    assert_eq!(select.remaining(), 0);
+    assert_eq!(select.select().await, None);
+
+    // BIASED
+    let (mut send1, mut recv1) = channel::bounded::<u8>(8);
+    let (mut send2, mut recv2) = channel::unbounded::<u8>();
+
+    let mut select = Select::new(Fairness::Biased)
+        .recv(&mut recv1, |data| data)
+        .recv(&mut recv2, |data| data);
+
+    for i in 0..4 {
+        assert_eq!(send1.try_send(i), Ok(()));
+        assert_eq!(send2.try_send(i), Ok(()));
+    }
+
+    for i in 0..4 {
+        assert_eq!(select.select().await, Some((0, Ok(i))));
+    }
+
+    for i in 0..4 {
+        assert_eq!(select.select().await, Some((1, Ok(i))));
+    }
+
+    // ROUND ROBIN
+    let (mut send1, mut recv1) = bounded::new::<u8>(8);
+    let (mut send2, mut recv2) = mpmc::unbounded::<u8>();
+
+    let mut select = Select::new(Fairness::RoundRobin)
+        .recv(&mut recv1, |data| data)
+        .recv(&mut recv2, |data| data);
+
+    for i in 0..4 {
+        assert_eq!(send1.try_send(i), Ok(()));
+        assert_eq!(send2.try_send(i), Ok(()));
+    }
+
+    for i in 0..4 {
+        assert_eq!(select.select().await, Some((0, Ok(i))));
+        assert_eq!(select.select().await, Some((1, Ok(i))));
+    }
+
+    // RANDOM
+    let (mut send1, mut recv1) = bounded::new::<u8>(8);
+    let (mut send2, mut recv2) = bounded::new::<u8>(8);
+
+    let select = Select::new(Fairness::Random)
+        .recv(&mut recv1, |data| data)
+        .recv(&mut recv2, |data| data + 8);
+
+    for i in 0..8 {
+        assert_eq!(send1.try_send(i), Ok(()));
+        assert_eq!(send2.try_send(i), Ok(()));
+    }
+
+    drop(send1);
+    drop(send2);
+
+    let mut received = select
+        .filter_map(|(_, res)| futures_util::future::ready(res.ok()))
+        .collect::<Vec<_>>()
+        .await;
+
+    received.sort();
+    assert_eq!(received, (0..16).collect::<Vec<_>>());
+
+    // LAGGED BRANCH
+    let (mut send, mut recv) = broadcast::new::<u8>(1);
+
+    let mut select = Select::new(Fairness::Biased).recv(&mut recv, |data| data);
+
+    assert_eq!(send.send(42), Ok(()));
+    assert_eq!(send.send(24), Ok(()));
+
+    assert_eq!(
+        select.select().await,
+        Some((0, Err(ReceiveError::Lagged(1))))
+    );
+    assert_eq!(select.select().await, Some((0, Ok(24))));
+    assert_eq!(select.remaining(), 1);
+}
+
+#[test]
+fn blocking() {
+    let (mut send1, mut recv1) = bounded::new::<u32>(1);
+    let (mut send2, mut recv2) = once::new::<u32>();
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..100 {
+            assert_eq!(send1.send_blocking(i), Ok(()));
+        }
+
+        assert_eq!(send2.send(100), Ok(()));
+    });
+
+    let mut select = Select::new(Fairness::Biased)
+        .recv(&mut recv1, |data| data)
+        .recv(&mut recv2, |data| data);
+
+    let mut received = vec![];
+    while let Some((_, res)) = select.select_blocking() {
+        if let Ok(data) = res {
+            received.push(data);
+        }
+    }
+
+    thread.join().unwrap();
+
+    received.sort();
+    assert_eq!(received, (0..=100).collect::<Vec<_>>());
+}
@@ -0,0 +1,136 @@
+#![feature(async_await)]
+
+use std::task::Poll;
+use std::time::Duration;
+
+use aktoro_channel::timer::*;
+use aktoro_channel::*;
+use futures_util::poll;
+
+#[runtime::test]
+async fn test() {
+    // SEND TIMEOUT
+    let timer = ManualTimer::new();
+    let (mut send, mut recv) = bounded::new::<u8>(1);
+
+    assert_eq!(send.try_send(42), Ok(()));
+
+    {
+        let mut fut = send.send_within(24, Duration::from_secs(1), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        timer.advance(Duration::from_millis(500));
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        timer.advance(Duration::from_millis(500));
+        assert_eq!(fut.await, Err(SendError::Timeout(24)));
+    }
+
+    // SEND DEADLINE
+    {
+        let deadline = timer.now() + Duration::from_secs(1);
+        let mut fut = send.send_deadline(24, deadline, &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(recv.try_recv(), Ok(42));
+        assert_eq!(fut.await, Ok(()));
+    }
+
+    // RECV TIMEOUT
+    assert_eq!(
+        recv.recv_within(Duration::from_secs(1), &timer).await,
+        Ok(24)
+    );
+
+    {
+        let mut fut = recv.recv_within(Duration::from_secs(1), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        timer.advance(Duration::from_secs(1));
+        assert_eq!(fut.await, Err(ReceiveError::Timeout));
+    }
+
+    // RECV DEADLINE
+    {
+        let deadline = timer.now() + Duration::from_secs(1);
+        let mut fut = recv.recv_deadline(deadline, &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        drop(send);
+        assert_eq!(fut.await, Err(ReceiveError::Closed));
+    }
+
+    // EXPIRED DEADLINE
+    let (mut send, mut recv) = channel::bounded::<u8>(1);
+    let deadline = timer.now();
+
+    assert_eq!(send.send_deadline(42, deadline, &timer).await, Ok(()));
+    assert_eq!(
+        send.send_deadline(24, deadline, &timer).await,
+        Err(SendError::Timeout(24))
+    );
+
+    assert_eq!(recv.recv_deadline(deadline, &timer).await, Ok(42));
+    assert_eq!(
+        recv.recv_deadline(deadline, &timer).await,
+        Err(ReceiveError::Timeout)
+    );
+
+    // THREAD TIMER
+    let timer = ThreadTimer::new();
+    let (mut send, mut recv) = unbounded::new::<u8>();
+
+    assert_eq!(
+        recv.recv_within(Duration::from_millis(10), timer.clone())
+            .await,
+        Err(ReceiveError::Timeout)
+    );
+
+    assert_eq!(send.send(42), Ok(()));
+    assert_eq!(
+        recv.recv_within(Duration::from_millis(10), &timer).await,
+        Ok(42)
+    );
+
+    let (mut send, _recv) = mpmc::bounded::<u8>(1);
+
+    assert_eq!(send.try_send(42), Ok(()));
+    assert_eq!(
+        send.send_within(24, Duration::from_millis(10), &timer)
+            .await,
+        Err(SendError::Timeout(24))
+    );
+
+    // CANCELLED TIMEOUT
+    let (mut send, mut recv) = unbounded::new::<u8>();
+
+    {
+        let mut fut = recv.recv_within(Duration::from_secs(3600), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+        assert_eq!(format!("{:?}", timer), "ThreadTimer { deadlines: 1 }");
+    }
+
+    assert_eq!(format!("{:?}", timer), "ThreadTimer { deadlines: 0 }");
+
+    {
+        let mut fut = recv.recv_within(Duration::from_secs(3600), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        assert_eq!(send.send(42), Ok(()));
+        assert_eq!(fut.await, Ok(42));
+    }
+
+    assert_eq!(format!("{:?}", timer), "ThreadTimer { deadlines: 0 }");
+
+    // DEFAULT THREAD TIMER
+    let timer = ThreadTimer::default();
+    let timer2 = ThreadTimer::default();
+
+    {
+        let mut fut = recv.recv_within(Duration::from_secs(3600), &timer);
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+        assert_eq!(format!("{:?}", timer2), "ThreadTimer { deadlines: 1 }");
+    }
+
+    assert_eq!(format!("{:?}", timer2), "ThreadTimer { deadlines: 0 }");
+}
@@ -1,6 +1,10 @@
 #![feature(async_await)]
 
+#[macro_use]
+mod common;
+
 use std::task::Poll;
+use std::time::Duration;
 
 use aktoro_channel::*;
 use futures_util::poll;
@@ -10,6 +14,8 @@ use futures_util::StreamExt;
 type Sender = unbounded::Sender<u8>;
 type Receiver = unbounded::Receiver<u8>;
 
+channel_helpers!(send);
+
 #[runtime::test]
 async fn test() {
     // NORMAL
@@ -25,6 +31,35 @@ async fn test() {
     recv_ok(24, &mut recv);
     recv_empty(&mut recv);
 
+    // RECEIPTS
+    let (mut send, mut recv) = unbounded::new::<u8>();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    let mut receipt = send.send_notify(42).unwrap();
+    assert_eq!(poll!(&mut receipt), Poll::Pending);
+
+    recv_ok(42, &mut recv);
+    assert_eq!(receipt.await, Ok(()));
+
+    let receipt = send.send_notify(24).unwrap();
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(receipt.await, Ok(()));
+
+    let receipt = send.send_notify(16).unwrap();
+    assert_eq!(recv.close(), Ok(()));
+    recv_ok(16, &mut recv);
+    assert_eq!(receipt.await, Ok(()));
+
+    let (mut send, recv) = unbounded::new::<u8>();
+
+    let receipt = send.send_notify(42).unwrap();
+    drop(recv);
+    assert_eq!(receipt.await, Err(ReceiveError::Closed));
+
+    assert_eq!(send.send_notify(24).unwrap_err(), SendError::Closed(24));
+
     // SINK
     let (mut send, mut recv) = unbounded::new::<u8>();
 
@@ -51,6 +86,27 @@ async fn test() {
     assert_eq!(recv.next().await, Some(24));
     assert_eq!(poll!(recv.next()), Poll::Pending);
 
+    // RECV FUTURE
+    let (mut send, mut recv) = unbounded::new::<u8>();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    {
+        let mut fut = recv.recv();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        send_ok(42, &mut send);
+
+        assert_eq!(fut.await, Ok(42));
+    }
+
+    send_ok(24, &mut send);
+    assert_eq!(send.close(), Ok(()));
+
+    assert_eq!(recv.recv().await, Ok(24));
+    assert_eq!(recv.recv().await, Err(ReceiveError::Closed));
+
     // DISCONNECTING SEND
     let (mut send, mut recv) = unbounded::new::<u8>();
 
@@ -65,8 +121,8 @@ async fn test() {
         send_ok(24, &mut send);
 
         assert_eq!(send.disconnect(), Ok(()));
-        assert!(!send.closed);
-        assert!(send.disconnected);
+        assert!(!send.is_closed());
+        assert!(send.is_disconnected());
         assert_eq!(send.disconnect(), Err(DisconnectError::Disconnected));
 
         assert_eq!(send.close(), Err(CloseError::Disconnected));
@@ -75,7 +131,7 @@ async fn test() {
 
         assert_eq!(
             SinkExt::send(&mut send, 32).await,
-            Err(SendError::Disconnected(()))
+            Err(SendError::Disconnected(32))
         );
     }
 
@@ -98,8 +154,9 @@ async fn test() {
     send_ok(42, &mut send);
 
     assert_eq!(send.disconnect(), Ok(()));
-    assert!(!send.closed);
-    assert!(send.disconnected);
+    assert!(send.is_closed());
+    assert!(send.is_disconnected());
+    assert!(recv.is_closed());
     assert_eq!(send.disconnect(), Err(DisconnectError::Disconnected));
 
     assert_eq!(send.close(), Err(CloseError::Disconnected));
@@ -111,7 +168,7 @@ async fn test() {
 
     assert_eq!(
         SinkExt::send(&mut send, 32).await,
-        Err(SendError::Disconnected(()))
+        Err(SendError::Disconnected(32))
     );
 
     // CLOSING SEND
@@ -126,8 +183,8 @@ async fn test() {
         let mut send = send.clone();
 
         assert_eq!(send.close(), Ok(()));
-        assert!(send.closed);
-        assert!(!send.disconnected);
+        assert!(send.is_closed());
+        assert!(!send.is_disconnected());
         assert_eq!(send.close(), Err(CloseError::Closed));
 
         assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
@@ -135,14 +192,16 @@ async fn test() {
         send_closed(24, &mut send);
     }
 
+    assert!(send.is_closed());
+    assert!(recv.is_closed());
+
     assert_eq!(send.close(), Err(CloseError::Closed));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
 
     assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
-    // FIXME: assert_eq!(recv.close(), Err(CloseError::Closed));
-    assert_eq!(recv.close(), Ok(()));
-    assert!(recv.closed);
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+    assert!(recv.is_closed());
 
     recv_ok(42, &mut recv);
 
@@ -151,9 +210,30 @@ async fn test() {
 
     assert_eq!(
         SinkExt::send(&mut send, 32).await,
-        Err(SendError::Closed(()))
+        Err(SendError::Closed(32))
     );
 
+    // DISCONNECTING RECV
+    let (mut send, mut recv) = unbounded::new::<u8>();
+
+    send_is_default(&send);
+    recv_is_default(&mut recv);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(recv.disconnect(), Ok(()));
+    assert!(recv.is_closed());
+    assert!(recv.is_disconnected());
+    assert_eq!(recv.disconnect(), Err(DisconnectError::Disconnected));
+
+    assert_eq!(recv.close(), Err(CloseError::Disconnected));
+
+    recv_disconnected(&mut recv);
+    assert_eq!(recv.recv().await, Err(ReceiveError::Disconnected));
+    assert_eq!(recv.next().await, None);
+
+    send_closed(24, &mut send);
+
     // CLOSING RECV
     let (mut send, mut recv) = unbounded::new::<u8>();
 
@@ -163,13 +243,14 @@ async fn test() {
     send_ok(42, &mut send);
 
     assert_eq!(recv.close(), Ok(()));
-    assert!(recv.closed);
+    assert!(recv.is_closed());
+    assert!(send.is_closed());
     assert_eq!(recv.close(), Err(CloseError::Closed));
 
     assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
     assert_eq!(send.close(), Err(CloseError::Closed));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
 
     recv_ok(42, &mut recv);
 
@@ -190,10 +271,10 @@ async fn test() {
 
     recv_ok(42, &mut recv);
     recv_closed(&mut recv);
-    assert!(recv.closed);
+    assert!(recv.is_closed());
 
     assert_eq!(recv.close(), Err(CloseError::Closed));
-    assert!(recv.closed);
+    assert!(recv.is_closed());
 
     // DROPING RECV
     let (mut send, mut recv) = unbounded::new::<u8>();
@@ -206,52 +287,90 @@ async fn test() {
     drop(recv);
 
     send_closed(24, &mut send);
-    assert!(send.closed);
+    assert!(send.is_closed());
 
     assert_eq!(send.disconnect(), Err(DisconnectError::Closed));
     assert_eq!(send.close(), Err(CloseError::Closed));
-    assert!(send.closed);
-    assert!(!send.disconnected);
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
 }
 
-fn send_is_default(send: &Sender) {
-    assert!(!send.closed);
-    assert!(!send.disconnected);
-}
+#[test]
+fn threads() {
+    let (send, mut recv) = unbounded::new::<u32>();
+
+    let threads = (0..4)
+        .map(|n| {
+            let mut send = send.clone();
+            std::thread::spawn(move || {
+                for i in 0..1000 {
+                    assert_eq!(send.send(n * 1000 + i), Ok(()));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
 
-fn recv_is_default(recv: &mut Receiver) {
-    assert!(!recv.closed);
-    recv_empty(recv);
-}
+    drop(send);
+    for thread in threads {
+        thread.join().unwrap();
+    }
 
-fn send_ok(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Ok(()));
-    assert!(!send.closed);
-    assert!(!send.disconnected);
-}
+    let mut received = vec![];
+    while let Ok(data) = recv.try_recv() {
+        received.push(data);
+    }
 
-fn send_disconnected(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Err(SendError::Disconnected(data)));
-    assert!(!send.closed);
-    assert!(send.disconnected);
+    received.sort();
+    assert_eq!(received, (0..4000).collect::<Vec<_>>());
+    assert!(recv.is_closed());
 }
 
-fn send_closed(data: u8, send: &mut Sender) {
-    assert_eq!(send.send(data), Err(SendError::Closed(data)));
-    assert!(send.closed);
-    assert!(!send.disconnected);
-}
+#[test]
+fn single_sender() {
+    let (mut send, mut recv) = unbounded::with_max_senders::<u32>(1);
+    assert_eq!(send.try_clone().unwrap_err(), CloneError::Limit);
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..1000 {
+            assert_eq!(send.send(i), Ok(()));
+        }
+    });
+
+    let mut received = vec![];
+    loop {
+        match recv.try_recv() {
+            Ok(data) => received.push(data),
+            Err(ReceiveError::Empty) => std::thread::yield_now(),
+            Err(_) => break,
+        }
+    }
 
-fn recv_ok(data: u8, recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Ok(data));
+    thread.join().unwrap();
+    assert_eq!(received, (0..1000).collect::<Vec<_>>());
 }
 
-fn recv_empty(recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Err(ReceiveError::Empty));
-    assert!(!recv.closed);
-}
+#[test]
+fn blocking() {
+    let (mut send, mut recv) = unbounded::new::<u32>();
+
+    assert_eq!(
+        recv.recv_timeout(Duration::from_millis(10)),
+        Err(ReceiveError::Timeout)
+    );
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..1000 {
+            assert_eq!(send.send(i), Ok(()));
+            if i % 100 == 0 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    });
+
+    for i in 0..1000 {
+        assert_eq!(recv.recv_blocking(), Ok(i));
+    }
 
-fn recv_closed(recv: &mut Receiver) {
-    assert_eq!(recv.try_recv(), Err(ReceiveError::Closed));
-    assert!(recv.closed);
+    thread.join().unwrap();
+    assert_eq!(recv.recv_blocking(), Err(ReceiveError::Closed));
 }
@@ -0,0 +1,209 @@
+#![feature(async_await)]
+
+use std::task::Poll;
+use std::time::Duration;
+
+use aktoro_channel::*;
+use futures_util::poll;
+use futures_util::SinkExt;
+use futures_util::StreamExt;
+
+type Sender = watch::Sender<u8>;
+type Receiver = watch::Receiver<u8>;
+
+#[runtime::test]
+async fn test() {
+    // NORMAL
+    let (mut send, mut recv) = watch::new::<u8>(0);
+
+    send_is_default(&send);
+    recv_is_default(&recv);
+    assert_eq!(*recv.borrow(), 0);
+
+    send_ok(42, &mut send);
+
+    recv_changed(42, &mut recv).await;
+    recv_unchanged(42, &mut recv).await;
+
+    // SKIPPING VALUES
+    let (mut send, mut recv) = watch::new::<u8>(0);
+    let mut recv2 = recv.clone();
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+
+    recv_changed(24, &mut recv).await;
+    recv_unchanged(24, &mut recv).await;
+
+    send_ok(16, &mut send);
+
+    recv_changed(16, &mut recv).await;
+    recv_changed(16, &mut recv2).await;
+
+    // CHANGED FUTURE
+    let (mut send, mut recv) = watch::new::<u8>(0);
+
+    {
+        let mut fut = recv.changed();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        send_ok(42, &mut send);
+
+        assert_eq!(fut.await, Ok(()));
+    }
+
+    assert_eq!(*recv.borrow(), 42);
+    assert!(!recv.has_changed());
+
+    // SINK
+    let (mut send, mut recv) = watch::new::<u8>(0);
+
+    assert_eq!(SinkExt::send(&mut send, 42).await, Ok(()));
+    recv_changed(42, &mut recv).await;
+
+    // STREAM
+    let (mut send, mut recv) = watch::new::<u8>(0);
+
+    send_ok(42, &mut send);
+    send_ok(24, &mut send);
+
+    assert_eq!(recv.next().await, Some(24));
+    assert_eq!(poll!(recv.next()), Poll::Pending);
+
+    send_ok(16, &mut send);
+    drop(send);
+
+    assert_eq!(recv.next().await, Some(16));
+    assert_eq!(recv.next().await, None);
+
+    // DISCONNECTING RECV
+    let (mut send, mut recv) = watch::new::<u8>(0);
+    let mut recv2 = recv.clone();
+
+    assert_eq!(recv2.disconnect(), Ok(()));
+    assert!(!recv2.is_closed());
+    assert!(recv2.is_disconnected());
+    assert_eq!(recv2.disconnect(), Err(DisconnectError::Disconnected));
+    assert_eq!(recv2.close(), Err(CloseError::Disconnected));
+
+    send_ok(42, &mut send);
+
+    assert!(!recv2.has_changed());
+    assert_eq!(recv2.changed().await, Err(ReceiveError::Disconnected));
+
+    recv_changed(42, &mut recv).await;
+
+    assert_eq!(recv.disconnect(), Ok(()));
+    assert!(send.is_closed());
+
+    send_closed(24, &mut send);
+
+    // CLOSING SEND
+    let (mut send, mut recv) = watch::new::<u8>(0);
+
+    send_ok(42, &mut send);
+
+    assert_eq!(send.close(), Ok(()));
+    assert!(send.is_closed());
+    assert!(recv.is_closed());
+    assert_eq!(send.close(), Err(CloseError::Closed));
+    assert_eq!(recv.close(), Err(CloseError::Closed));
+
+    send_closed(24, &mut send);
+
+    assert!(recv.has_changed());
+    assert_eq!(recv.changed().await, Ok(()));
+    assert_eq!(*recv.borrow(), 42);
+    assert_eq!(recv.changed().await, Err(ReceiveError::Closed));
+
+    // DROPING SEND
+    let (send, mut recv) = watch::new::<u8>(0);
+    let send2 = send.clone();
+
+    drop(send);
+    assert!(!recv.is_closed());
+
+    {
+        let mut fut = recv.changed();
+        assert_eq!(poll!(&mut fut), Poll::Pending);
+
+        drop(send2);
+
+        assert_eq!(fut.await, Err(ReceiveError::Closed));
+    }
+
+    assert!(recv.is_closed());
+    assert_eq!(*recv.borrow(), 0);
+
+    // DROPING RECV
+    let (mut send, recv) = watch::new::<u8>(0);
+    let recv2 = recv.clone();
+
+    drop(recv);
+    send_ok(42, &mut send);
+
+    drop(recv2);
+    send_closed(24, &mut send);
+}
+
+#[test]
+fn blocking() {
+    let (mut send, mut recv) = watch::new::<u8>(0);
+
+    assert_eq!(
+        recv.changed_timeout(Duration::from_millis(10)),
+        Err(ReceiveError::Timeout)
+    );
+
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(send.send(42), Ok(()));
+        send
+    });
+
+    assert_eq!(recv.changed_blocking(), Ok(()));
+    assert_eq!(*recv.borrow(), 42);
+
+    let send = thread.join().unwrap();
+    drop(send);
+
+    assert_eq!(
+        recv.changed_timeout(Duration::from_millis(10)),
+        Err(ReceiveError::Closed)
+    );
+}
+
+fn send_is_default(send: &Sender) {
+    assert!(!send.is_closed());
+    assert!(!send.is_disconnected());
+}
+
+fn recv_is_default(recv: &Receiver) {
+    assert!(!recv.is_closed());
+    assert!(!recv.is_disconnected());
+    assert!(!recv.has_changed());
+}
+
+fn send_ok(data: u8, send: &mut Sender) {
+    assert_eq!(send.send(data), Ok(()));
+    assert!(!send.is_closed());
+    assert!(!send.is_disconnected());
+}
+
+fn send_closed(data: u8, send: &mut Sender) {
+    assert_eq!(send.send(data), Err(SendError::Closed(data)));
+    assert!(send.is_closed());
+    assert!(!send.is_disconnected());
+}
+
+async fn recv_changed(data: u8, recv: &mut Receiver) {
+    assert!(recv.has_changed());
+    assert_eq!(poll!(recv.changed()), Poll::Ready(Ok(())));
+    assert_eq!(*recv.borrow(), data);
+}
+
+async fn recv_unchanged(data: u8, recv: &mut Receiver) {
+    assert!(!recv.has_changed());
+    assert_eq!(poll!(recv.changed()), Poll::Pending);
+    assert_eq!(*recv.borrow(), data);
+}